@@ -21,6 +21,8 @@ from google.protobuf.json_format import MessageToDict
 
 import grpc
 
+import cao_common_pb2
+import cao_script_pb2
 from cao_script_pb2 import Empty
 from cao_script_pb2_grpc import ScriptingStub
 
@@ -45,6 +47,53 @@ async def get_schema():
         ) from err
 
 
+@router.get("/my-script-errors", response_model=List[Dict])
+async def get_my_script_errors(current_user_id=Depends(get_current_user_id)):
+    """
+    return the last runtime errors of the current user's scripts, oldest first
+    """
+    msg = cao_common_pb2.Uuid()
+    msg.data = UUID(current_user_id).bytes
+    stub = ScriptingStub(await queen_channel())
+    res = await stub.GetUserScriptErrors(msg)
+    return MessageToDict(
+        res, including_default_value_fields=True, preserving_proto_field_name=False
+    ).get("errors", [])
+
+
+@router.get("/entity-script-errors", response_model=List[Dict])
+async def get_entity_script_errors(
+    entity_id: int = Query(...), current_user_id=Depends(get_current_user_id)
+):
+    """
+    return the last runtime errors of the script running on the given entity of the current
+    user, oldest first
+    """
+    msg = cao_script_pb2.EntityScriptErrorsQuery()
+    msg.userId.data = UUID(current_user_id).bytes
+    msg.entityId = entity_id
+    stub = ScriptingStub(await queen_channel())
+    try:
+        res = await stub.GetEntityScriptErrors(msg)
+    except grpc.aio.AioRpcError as err:
+        if err.code() == grpc.StatusCode.NOT_FOUND:
+            raise HTTPException(
+                status_code=status.HTTP_404_NOT_FOUND, detail="Entity not found"
+            ) from err
+        if err.code() == grpc.StatusCode.PERMISSION_DENIED:
+            raise HTTPException(
+                status_code=status.HTTP_403_FORBIDDEN,
+                detail="Entity is not owned by the current user",
+            ) from err
+        logging.exception("Unhandled rpc error")
+        raise HTTPException(
+            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+        ) from err
+    return MessageToDict(
+        res, including_default_value_fields=True, preserving_proto_field_name=False
+    ).get("errors", [])
+
+
 def _compile_caolang_program(prog_json: str):
     try:
         compilation_unit = cao_lang.CompilationUnit.from_json(prog_json)
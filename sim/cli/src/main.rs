@@ -0,0 +1,184 @@
+//! Run the simulation locally, without networking.
+//!
+//! ```txt
+//! caolo-sim-cli [--config <game_config.yaml>] [--seed <u64>] [--users <n>] [--ticks <n>]
+//!               [--snapshot-every <n>] [--snapshot-dir <dir>]
+//! ```
+use std::{
+    env, fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use caolo_sim::prelude::*;
+use futures_lite::future::block_on;
+use tracing::{debug, info};
+
+#[derive(Debug)]
+struct Args {
+    config: Option<PathBuf>,
+    seed: Option<u64>,
+    n_users: usize,
+    n_ticks: u64,
+    /// Dump a JSON snapshot every `snapshot_every` ticks. 0 disables snapshots
+    snapshot_every: u64,
+    snapshot_dir: PathBuf,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            config: None,
+            seed: None,
+            n_users: 10,
+            n_ticks: 100,
+            snapshot_every: 0,
+            snapshot_dir: PathBuf::from("snapshots"),
+        }
+    }
+}
+
+impl Args {
+    fn parse() -> anyhow::Result<Self> {
+        let mut result = Self::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing value of argument {}", arg))
+            };
+            match arg.as_str() {
+                "--config" => result.config = Some(PathBuf::from(value()?)),
+                "--seed" => result.seed = Some(value()?.parse().context("Failed to parse seed")?),
+                "--users" => result.n_users = value()?.parse().context("Failed to parse users")?,
+                "--ticks" => result.n_ticks = value()?.parse().context("Failed to parse ticks")?,
+                "--snapshot-every" => {
+                    result.snapshot_every =
+                        value()?.parse().context("Failed to parse snapshot-every")?
+                }
+                "--snapshot-dir" => result.snapshot_dir = PathBuf::from(value()?),
+                _ => bail!("Unrecognized argument {}", arg),
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Default)]
+struct TickStats {
+    total: Duration,
+    min: Option<Duration>,
+    max: Duration,
+    scripts_ran: u64,
+    scripts_errored: u64,
+    scripts_skipped: u64,
+}
+
+impl TickStats {
+    fn push(&mut self, duration: Duration) {
+        self.total += duration;
+        self.min = Some(self.min.map_or(duration, |m| m.min(duration)));
+        self.max = self.max.max(duration);
+    }
+}
+
+fn load_config(args: &Args) -> anyhow::Result<GameConfig> {
+    let mut config: GameConfig = match args.config.as_ref() {
+        Some(path) => {
+            let f = fs::File::open(path)
+                .with_context(|| format!("Failed to open config file {:?}", path))?;
+            serde_yaml::from_reader(f).context("Failed to parse GameConfig")?
+        }
+        None => Default::default(),
+    };
+    if args.seed.is_some() {
+        config.seed = args.seed;
+    }
+    Ok(config)
+}
+
+fn save_snapshot(world: &World, dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("world_{:06}.json", world.time()));
+    let f = fs::File::create(&path).with_context(|| format!("Failed to create {:?}", path))?;
+    serde_json::to_writer(BufWriter::new(f), world).context("Failed to serialize world")?;
+    debug!("Saved snapshot {:?}", path);
+    Ok(())
+}
+
+fn print_summary(world: &World, stats: &TickStats, n_ticks: u64) {
+    let state_hash = world.state_hash();
+    println!("ticks:          {}", n_ticks);
+    println!("world time:     {}", world.time());
+    println!("total time:     {:.2?}", stats.total);
+    if n_ticks > 0 {
+        println!("mean tick:      {:.2?}", stats.total / n_ticks as u32);
+    }
+    println!("min tick:       {:.2?}", stats.min.unwrap_or_default());
+    println!("max tick:       {:.2?}", stats.max);
+    println!("scripts ran:    {}", stats.scripts_ran);
+    println!("script errors:  {}", stats.scripts_errored);
+    println!("out of cpu:     {}", stats.scripts_skipped);
+    println!("users:          {}", world.list_users().count());
+    println!(
+        "bots:           {}",
+        world.view::<EntityId, Bot>().iter().count()
+    );
+    println!(
+        "structures:     {}",
+        world.view::<EntityId, Structure>().iter().count()
+    );
+    println!(
+        "resources:      {}",
+        world.view::<EntityId, ResourceComponent>().iter().count()
+    );
+    println!(
+        "scripts:        {}",
+        world
+            .view::<ScriptId, CompiledScriptComponent>()
+            .iter()
+            .count()
+    );
+    println!("state hash:     {:016x}", state_hash.hash);
+}
+
+fn main() -> anyhow::Result<()> {
+    let collector = tracing_subscriber::fmt()
+        .without_time()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .finish();
+    tracing::subscriber::set_global_default(collector)?;
+
+    let args = Args::parse()?;
+    info!("Running with {:#?}", args);
+    let config = load_config(&args)?;
+
+    let mut executor = SimpleExecutor;
+    let mut world = block_on(executor.initialize(config));
+    caolo_sim::init::init_world_entities(&mut world, args.n_users);
+
+    let mut stats = TickStats::default();
+    for _ in 0..args.n_ticks {
+        if args.snapshot_every > 0 && world.time() % args.snapshot_every == 0 {
+            save_snapshot(&world, &args.snapshot_dir)?;
+        }
+
+        let start = Instant::now();
+        let run_result = block_on(executor.forward_bots(&world)).unwrap();
+        stats.scripts_ran += run_result.num_scripts_ran;
+        stats.scripts_errored += run_result.num_scripts_errored;
+        stats.scripts_skipped += run_result.num_scripts_skipped;
+        block_on(executor.apply_intents(&mut world, run_result.intents)).unwrap();
+        let duration = Instant::now() - start;
+        stats.push(duration);
+        debug!("Tick {} done in {:.2?}", world.time(), duration);
+    }
+    if args.snapshot_every > 0 {
+        save_snapshot(&world, &args.snapshot_dir)?;
+    }
+
+    print_summary(&world, &stats, args.n_ticks);
+    Ok(())
+}
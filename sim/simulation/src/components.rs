@@ -4,12 +4,16 @@ mod bot_components;
 mod resources;
 mod rooms;
 mod script_components;
+mod structure_components;
+mod world_rng;
 pub use bot_components::*;
 pub use resources::*;
 pub use rooms::*;
 pub use script_components::*;
+pub use structure_components::*;
+pub use world_rng::*;
 
-use crate::indices::{EntityId, Room, UserId, WorldPosition};
+use crate::indices::{EntityId, Room, ScriptId, UserId, WorldPosition};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
@@ -40,13 +44,28 @@ pub struct EnergyComponent {
     pub energy_max: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SpawnComponent {
     /// Time to spawn the current entity
     pub time_to_spawn: i16,
     /// Currently spawning entity
     pub spawning: Option<EntityId>,
+    /// Queue a default bot whenever the queue is empty
+    pub continuous: bool,
+    /// Script of the bots spawned here. If not set bots run the default script of the owner
+    pub bot_script: Option<ScriptId>,
+}
+
+impl Default for SpawnComponent {
+    fn default() -> Self {
+        Self {
+            time_to_spawn: 0,
+            spawning: None,
+            continuous: true,
+            bot_script: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -73,6 +92,7 @@ pub struct EnergyRegenComponent {
 #[serde(rename_all = "camelCase")]
 pub struct SpawnBotComponent {
     pub bot: Bot,
+    pub body: BotBody,
 }
 
 // TODO:
@@ -99,6 +119,16 @@ impl Default for UserProperties {
     }
 }
 
+/// Number of instructions the scripts of a user may execute.
+///
+/// Refilled every tick, unused budget accumulates up to a limit.
+/// See [GameConfig](game_config::GameConfig) for the parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuBudget {
+    pub available: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Rooms(pub Vec<Room>);
 
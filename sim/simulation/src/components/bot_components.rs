@@ -1,3 +1,4 @@
+use super::{Resource, ResourceMap};
 use crate::indices::{EntityId, RoomPosition, ScriptId, WorldPosition};
 use arrayvec::{ArrayString, ArrayVec};
 
@@ -9,6 +10,45 @@ pub struct MeleeAttackComponent {
     pub strength: u16,
 }
 
+/// Range of the ranged attacks of bots
+pub const RANGED_ATTACK_RANGE: u16 = 3;
+
+/// Damage of ranged attacks falls off linearly from `strength` next to the attacker to
+/// `strength / range` at the edge of the range
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RangedAttackComponent {
+    pub strength: u16,
+    pub range: u16,
+}
+
+impl RangedAttackComponent {
+    /// Damage dealt to a target `distance` tiles away, 0 if the target is out of range.
+    /// Attacks with 0 range deal no damage.
+    pub fn damage_at(&self, distance: u32) -> u16 {
+        let range = self.range as u32;
+        if range == 0 || distance > range {
+            return 0;
+        }
+        let distance = distance.max(1);
+        (self.strength as u32 * (range + 1 - distance) / range) as u16
+    }
+}
+
+/// Hp restored by a heal of the entity
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HealComponent {
+    pub amount: u16,
+}
+
+/// Flat reduction of the damage of every attack the entity takes
+#[derive(Debug, Clone, Serialize, Deserialize, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmorComponent {
+    pub armor: u16,
+}
+
 /// Has a body so it's not `null` when serializing
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -24,13 +64,72 @@ pub struct DecayComponent {
     pub time_remaining: u8,
 }
 
+/// Resources carried by an entity. All resource types share the same capacity
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CarryComponent {
-    pub carry: u16,
+    pub resources: ResourceMap,
     pub carry_max: u16,
 }
 
+impl CarryComponent {
+    pub fn new(carry_max: u16) -> Self {
+        Self {
+            resources: ResourceMap::default(),
+            carry_max,
+        }
+    }
+
+    /// Total amount carried
+    pub fn carry(&self) -> u16 {
+        self.resources.total()
+    }
+
+    pub fn free(&self) -> u16 {
+        self.carry_max.saturating_sub(self.carry())
+    }
+
+    /// Add at most `amount` of `ty`, limited by the free capacity. Returns the amount added
+    pub fn add(&mut self, ty: Resource, amount: u16) -> u16 {
+        let added = amount.min(self.free());
+        self.resources.add(ty, added);
+        added
+    }
+}
+
+/// Describes the bot a spawn produces
+///
+/// Every part of the body is priced in the energy of the spawn, see
+/// [BotBodyCost](crate::components::game_config::BotBodyCost).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BotBody {
+    pub hp: u16,
+    pub carry: u16,
+    pub melee: u16,
+    /// Strength of ranged attacks, see [RangedAttackComponent]
+    pub ranged: u16,
+    pub armor: u16,
+    /// Hp restored by a heal, see [HealComponent]
+    pub heal: u16,
+    /// Ticks between losing hp to decay. Longer lived bots cost more
+    pub decay_interval: u8,
+}
+
+impl Default for BotBody {
+    fn default() -> Self {
+        Self {
+            hp: 100,
+            carry: 150,
+            melee: 0,
+            ranged: 0,
+            armor: 0,
+            heal: 0,
+            decay_interval: 10,
+        }
+    }
+}
+
 /// Entity - Script join table
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Copy)]
 #[serde(rename_all = "camelCase")]
@@ -1,15 +1,41 @@
+use super::{BotBody, StructureType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameConfig {
     pub world_radius: u32,
     pub room_radius: u32,
     pub execution_limit: u32,
     pub target_tick_ms: u64,
     /// Unique ID of this world instance
+    ///
+    /// If empty, a tag is generated from the world RNG on initialization
     pub queen_tag: String,
+    /// Seed of the world RNG. If set, world generation and simulation are deterministic
+    pub seed: Option<u64>,
     /// maximum number of steps pathfinding can test
     pub path_finding_limit: u32,
+    /// Scripts are charged one instruction per this many pathfinding steps
+    pub path_finding_steps_per_instruction: u32,
+    /// Number of script errors stored per entity and per user
+    pub script_error_history_len: usize,
+    /// Instructions a user's scripts may execute in a tick, per user level
+    pub cpu_per_level: u64,
+    /// Unused cpu budget accumulates up to `cpu_bucket_ticks` ticks worth of budget
+    pub cpu_bucket_ticks: u64,
+    /// Maximum size of the script memory of an entity, in bytes
+    pub entity_memory_limit: usize,
+    /// Maximum size of the script memory of a user, in bytes
+    pub user_memory_limit: usize,
+    /// Maximum size of a single script message, in bytes
+    pub message_size_limit: usize,
+    /// Maximum total size of the messages a user's scripts may send in a tick, in bytes
+    pub user_message_limit: usize,
+    /// Spawn energy the parts of a bot cost
+    pub bot_body_cost: BotBodyCost,
+    /// Energy needed to finish the construction site of a structure
+    pub construction_cost: ConstructionCost,
 }
 
 impl Default for GameConfig {
@@ -17,10 +43,129 @@ impl Default for GameConfig {
         Self {
             execution_limit: 128,
             target_tick_ms: 100,
-            queen_tag: uuid::Uuid::new_v4().to_string(),
+            queen_tag: String::new(),
+            seed: None,
             world_radius: 4,
             room_radius: 8,
             path_finding_limit: 1000,
+            path_finding_steps_per_instruction: 10,
+            script_error_history_len: 10,
+            cpu_per_level: 128 * 100,
+            cpu_bucket_ticks: 10,
+            entity_memory_limit: 1024,
+            user_memory_limit: 16 * 1024,
+            message_size_limit: 256,
+            user_message_limit: 4 * 1024,
+            bot_body_cost: BotBodyCost::default(),
+            construction_cost: ConstructionCost::default(),
         }
     }
 }
+
+impl GameConfig {
+    /// Cpu budget a user of the given level receives every tick
+    pub fn cpu_per_tick(&self, level: u16) -> u64 {
+        self.cpu_per_level * level.max(1) as u64
+    }
+
+    /// Maximum cpu budget a user of the given level may accumulate
+    pub fn cpu_bucket_limit(&self, level: u16) -> u64 {
+        self.cpu_per_tick(level) * self.cpu_bucket_ticks.max(1)
+    }
+}
+
+/// Energy cost of bot bodies. The default body costs 500 energy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotBodyCost {
+    /// Cost of every bot
+    pub base: u32,
+    pub per_hp: u32,
+    pub per_carry: u32,
+    pub per_melee: u32,
+    pub per_ranged: u32,
+    pub per_armor: u32,
+    pub per_heal: u32,
+    /// Cost of every tick between decays
+    pub per_decay_interval: u32,
+}
+
+impl Default for BotBodyCost {
+    fn default() -> Self {
+        Self {
+            base: 100,
+            per_hp: 1,
+            per_carry: 1,
+            per_melee: 2,
+            per_ranged: 3,
+            per_armor: 2,
+            per_heal: 3,
+            per_decay_interval: 15,
+        }
+    }
+}
+
+impl BotBodyCost {
+    /// Saturates at `u32::MAX`, a cost no spawn can pay
+    pub fn cost(&self, body: &BotBody) -> u32 {
+        [
+            (self.per_hp, body.hp as u32),
+            (self.per_carry, body.carry as u32),
+            (self.per_melee, body.melee as u32),
+            (self.per_ranged, body.ranged as u32),
+            (self.per_armor, body.armor as u32),
+            (self.per_heal, body.heal as u32),
+            (self.per_decay_interval, body.decay_interval as u32),
+        ]
+        .iter()
+        .fold(self.base, |cost, (per_part, parts)| {
+            cost.saturating_add(per_part.saturating_mul(*parts))
+        })
+    }
+}
+
+/// Energy cost of the structures scripts can build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConstructionCost {
+    pub container: u16,
+    pub tower: u16,
+}
+
+impl Default for ConstructionCost {
+    fn default() -> Self {
+        Self {
+            container: 500,
+            tower: 1000,
+        }
+    }
+}
+
+impl ConstructionCost {
+    /// `None` if the structure can not be built by scripts
+    pub fn cost(&self, ty: StructureType) -> Option<u16> {
+        match ty {
+            StructureType::Spawn => None,
+            StructureType::Container => Some(self.container),
+            StructureType::Tower => Some(self.tower),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bot_body_cost_saturates() {
+        let body = BotBody::default();
+        let default_cost = BotBodyCost::default();
+        assert_eq!(default_cost.cost(&body), 500);
+
+        let cost = BotBodyCost {
+            per_hp: u32::MAX / 2,
+            ..Default::default()
+        };
+        assert_eq!(cost.cost(&body), u32::MAX);
+    }
+}
@@ -2,12 +2,19 @@ use cao_lang::prelude::Value;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
-#[derive(Debug, Serialize, Clone, Copy, Deserialize)]
+#[derive(Debug, Serialize, Clone, Copy, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "camelCase")]
 #[repr(u8)]
 pub enum Resource {
     Empty = 0,
     Energy = 1,
+    Mineral = 2,
+    Crystal = 3,
+}
+
+impl Resource {
+    /// Resources that can be mined and carried
+    pub const MINABLE: [Resource; 3] = [Resource::Energy, Resource::Mineral, Resource::Crystal];
 }
 
 impl Default for Resource {
@@ -26,6 +33,8 @@ impl TryFrom<Value> for Resource {
                 }
                 match i {
                     1 => Ok(Resource::Energy),
+                    2 => Ok(Resource::Mineral),
+                    3 => Ok(Resource::Crystal),
                     _ => Err(s),
                 }
             }
@@ -34,6 +43,8 @@ impl TryFrom<Value> for Resource {
     }
 }
 
+/// Type of a resource deposit. The `EnergyComponent` of the deposit holds the amount of the
+/// resource left, regardless of its type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourceComponent(pub Resource);
@@ -43,3 +54,44 @@ impl Default for ResourceComponent {
         Self(Resource::Energy)
     }
 }
+
+const RESOURCE_SLOTS: usize = Resource::Crystal as usize + 1;
+
+/// Small map of resource type to amount
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceMap([u16; RESOURCE_SLOTS]);
+
+impl ResourceMap {
+    pub fn get(&self, ty: Resource) -> u16 {
+        self.0[ty as usize]
+    }
+
+    /// Total amount of all resources
+    pub fn total(&self) -> u16 {
+        self.0
+            .iter()
+            .fold(0u16, |total, amount| total.saturating_add(*amount))
+    }
+
+    /// `Empty` can not be stored, adding it is a noop
+    pub fn add(&mut self, ty: Resource, amount: u16) {
+        if ty != Resource::Empty {
+            self.0[ty as usize] = self.0[ty as usize].saturating_add(amount);
+        }
+    }
+
+    /// Remove at most `amount` of `ty`. Returns the amount removed
+    pub fn remove(&mut self, ty: Resource, amount: u16) -> u16 {
+        let removed = self.0[ty as usize].min(amount);
+        self.0[ty as usize] -= removed;
+        removed
+    }
+
+    /// Iterate over the resources with a non-zero amount
+    pub fn iter(&self) -> impl Iterator<Item = (Resource, u16)> + '_ {
+        Resource::MINABLE
+            .iter()
+            .map(move |ty| (*ty, self.get(*ty)))
+            .filter(|(_, amount)| *amount > 0)
+    }
+}
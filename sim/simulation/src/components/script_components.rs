@@ -1,7 +1,8 @@
-use crate::indices::EntityId;
+use crate::indices::{EntityId, ScriptId};
 use cao_lang::{prelude, program::CaoProgram};
 use prelude::CaoIr;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 
 /// Currently does nothing as Cao-Lang not yet supports history
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -14,6 +15,103 @@ pub struct ScriptHistoryEntry {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ScriptHistory(());
 
+/// A failed script execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptError {
+    pub time: u64,
+    pub entity_id: EntityId,
+    pub script_id: ScriptId,
+    pub error: String,
+}
+
+/// The last few errors of an entity's or user's scripts, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptErrors(pub VecDeque<ScriptError>);
+
+impl ScriptErrors {
+    /// Push a new error, dropping the oldest ones if more than `limit` would be stored
+    pub fn push(&mut self, error: ScriptError, limit: usize) {
+        self.0.push_back(error);
+        while self.0.len() > limit {
+            self.0.pop_front();
+        }
+    }
+}
+
+/// A value scripts can store in their memory
+///
+/// Only scalars are stored, Objects and tables are rejected by `set_memory`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MemoryValue {
+    Integer(i64),
+    Floating(f64),
+    Text(String),
+}
+
+impl MemoryValue {
+    /// Number of bytes the value counts towards the memory limit
+    pub fn size(&self) -> usize {
+        match self {
+            MemoryValue::Integer(_) | MemoryValue::Floating(_) => 8,
+            MemoryValue::Text(s) => s.len(),
+        }
+    }
+}
+
+/// Values that scripts of an entity or user keep between ticks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptMemory(pub BTreeMap<String, MemoryValue>);
+
+impl ScriptMemory {
+    /// Number of bytes the memory counts towards the memory limit
+    pub fn size(&self) -> usize {
+        self.0.iter().map(|(k, v)| k.len() + v.size()).sum()
+    }
+
+    /// Set or, if `value` is `None`, remove a key.
+    ///
+    /// Returns false and leaves the memory unchanged if the result would be larger than
+    /// `limit`.
+    pub fn set(&mut self, key: String, value: Option<MemoryValue>, limit: usize) -> bool {
+        match value {
+            Some(value) => {
+                let old_size = self.0.get(&key).map(|v| key.len() + v.size()).unwrap_or(0);
+                let new_size = self.size() - old_size + key.len() + value.size();
+                if new_size > limit {
+                    return false;
+                }
+                self.0.insert(key, value);
+            }
+            None => {
+                self.0.remove(&key);
+            }
+        }
+        true
+    }
+}
+
+/// A message sent by a script, readable by the scripts of the same user in the next tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptMessage {
+    pub sender: EntityId,
+    pub payload: MemoryValue,
+}
+
+/// Messages sent directly to an entity in the last tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inbox(pub Vec<ScriptMessage>);
+
+/// Messages posted to the named channels of a user in the last tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserChannels(pub BTreeMap<String, Vec<ScriptMessage>>);
+
 /// Entities with Scripts
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,67 @@
+use super::{Resource, ResourceMap};
+use cao_lang::prelude::Value;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+#[derive(Debug, Serialize, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum StructureType {
+    #[default]
+    Spawn = 0,
+    Container = 1,
+    Tower = 2,
+}
+
+impl TryFrom<Value> for StructureType {
+    type Error = Value;
+    fn try_from(s: Value) -> Result<StructureType, Value> {
+        match s {
+            Value::Integer(0) => Ok(StructureType::Spawn),
+            Value::Integer(1) => Ok(StructureType::Container),
+            Value::Integer(2) => Ok(StructureType::Tower),
+            _ => Err(s),
+        }
+    }
+}
+
+/// A structure under construction.
+///
+/// The `EnergyComponent` of the site holds the energy unloaded into it, the site turns into the
+/// structure once it is full.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstructionSiteComponent(pub StructureType);
+
+/// Resources stored in a structure. All resource types share the same capacity
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryComponent {
+    pub resources: ResourceMap,
+    pub capacity: u16,
+}
+
+impl InventoryComponent {
+    pub fn new(capacity: u16) -> Self {
+        Self {
+            resources: ResourceMap::default(),
+            capacity,
+        }
+    }
+
+    /// Total amount stored
+    pub fn stored(&self) -> u16 {
+        self.resources.total()
+    }
+
+    pub fn free(&self) -> u16 {
+        self.capacity.saturating_sub(self.stored())
+    }
+
+    /// Add at most `amount` of `ty`, limited by the free capacity. Returns the amount added
+    pub fn add(&mut self, ty: Resource, amount: u16) -> u16 {
+        let added = amount.min(self.free());
+        self.resources.add(ty, added);
+        added
+    }
+}
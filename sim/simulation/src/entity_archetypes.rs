@@ -7,6 +7,10 @@ use crate::query;
 
 /// Initialize a spawn at the given position
 pub fn init_structure_spawn(id: EntityId, owner_id: Uuid, pos: WorldPosition, world: &mut World) {
+    init_structure(id, StructureType::Spawn, owner_id, pos, world)
+}
+
+fn init_spawn_components(id: EntityId, world: &mut World) {
     // TODO tweak these numbas
     query!(
         mutate world
@@ -14,12 +18,6 @@ pub fn init_structure_spawn(id: EntityId, owner_id: Uuid, pos: WorldPosition, wo
             EntityId, Structure, .insert(id);
             EntityId, SpawnComponent, .insert(id, SpawnComponent::default());
             EntityId, SpawnQueueComponent, .insert(id, SpawnQueueComponent::default());
-            EntityId, OwnedEntity, .insert(
-                id,
-                OwnedEntity {
-                    owner_id: UserId(owner_id),
-                }
-            );
             EntityId, EnergyComponent, .insert(
                 id,
                 EnergyComponent {
@@ -35,10 +33,87 @@ pub fn init_structure_spawn(id: EntityId, owner_id: Uuid, pos: WorldPosition, wo
                     hp_max: 500,
                 }
             );
+        }
+    );
+}
+
+/// Range of the ranged attack of towers
+pub const TOWER_RANGE: u16 = 5;
+/// Total amount of resources a container can hold
+pub const CONTAINER_CAPACITY: u16 = 10_000;
+
+type InitStructureTables = (
+    UnsafeView<EntityId, Structure>,
+    UnsafeView<EntityId, HpComponent>,
+    UnsafeView<EntityId, InventoryComponent>,
+    UnsafeView<EntityId, RangedAttackComponent>,
+);
+
+/// Initialize a container, used to stockpile any kind of resource
+pub fn init_structure_container(
+    id: EntityId,
+    (mut structures, mut hps, mut inventory, _): InitStructureTables,
+) {
+    structures.insert(id);
+    hps.insert(
+        id,
+        HpComponent {
+            hp: 1000,
+            hp_max: 1000,
+        },
+    );
+    inventory.insert(id, InventoryComponent::new(CONTAINER_CAPACITY));
+}
+
+/// Initialize a tower. Towers shoot with the `ranged_attack` of the script they run
+pub fn init_structure_tower(
+    id: EntityId,
+    (mut structures, mut hps, _, mut ranged): InitStructureTables,
+) {
+    structures.insert(id);
+    hps.insert(
+        id,
+        HpComponent {
+            hp: 2000,
+            hp_max: 2000,
+        },
+    );
+    ranged.insert(
+        id,
+        RangedAttackComponent {
+            strength: 30,
+            range: TOWER_RANGE,
+        },
+    );
+}
+
+/// Initialize a finished structure of the given type at the given position
+pub fn init_structure(
+    id: EntityId,
+    ty: StructureType,
+    owner_id: Uuid,
+    pos: WorldPosition,
+    world: &mut World,
+) {
+    match ty {
+        StructureType::Spawn => init_spawn_components(id, world),
+        StructureType::Container => {
+            init_structure_container(id, FromWorldMut::from_world_mut(world))
+        }
+        StructureType::Tower => init_structure_tower(id, FromWorldMut::from_world_mut(world)),
+    }
+    query!(
+        mutate world
+        {
+            EntityId, OwnedEntity, .insert(
+                id,
+                OwnedEntity {
+                    owner_id: UserId(owner_id),
+                }
+            );
             EntityId, PositionComponent, .insert(id, PositionComponent(pos));
             WorldPosition, EntityComponent, .insert(pos, EntityComponent(id))
                 .expect("entities_by_pos insert failed");
-
         }
     );
 }
@@ -48,19 +123,29 @@ type InitBotTables = (
     UnsafeView<EntityId, HpComponent>,
     UnsafeView<EntityId, DecayComponent>,
     UnsafeView<EntityId, CarryComponent>,
+    (
+        UnsafeView<EntityId, MeleeAttackComponent>,
+        UnsafeView<EntityId, RangedAttackComponent>,
+        UnsafeView<EntityId, ArmorComponent>,
+        UnsafeView<EntityId, HealComponent>,
+    ),
     UnsafeView<EntityId, PositionComponent>,
     UnsafeView<EntityId, OwnedEntity>,
     UnsafeView<EntityId, EntityScript>,
 );
+/// Initialize a bot with the given body.
+/// Bots only get the combat components their body has points in.
 pub fn init_bot(
     entity_id: EntityId,
     owner_id: Option<Uuid>,
     pos: WorldPosition,
+    body: BotBody,
     (
         mut bots,
         mut hps,
         mut decay,
         mut carry,
+        (mut melee, mut ranged, mut armor, mut heal),
         mut positions,
         mut owned,
         mut script_table,
@@ -71,25 +156,42 @@ pub fn init_bot(
     hps.insert(
         entity_id,
         HpComponent {
-            hp: 100,
-            hp_max: 100,
+            hp: body.hp,
+            hp_max: body.hp,
         },
     );
     decay.insert(
         entity_id,
         DecayComponent {
-            interval: 10,
-            time_remaining: 10,
+            interval: body.decay_interval,
+            time_remaining: body.decay_interval,
             hp_amount: 10,
         },
     );
-    carry.insert(
-        entity_id,
-        CarryComponent {
-            carry: 0,
-            carry_max: 150,
-        },
-    );
+    carry.insert(entity_id, CarryComponent::new(body.carry));
+    if body.melee > 0 {
+        melee.insert(
+            entity_id,
+            MeleeAttackComponent {
+                strength: body.melee,
+            },
+        );
+    }
+    if body.ranged > 0 {
+        ranged.insert(
+            entity_id,
+            RangedAttackComponent {
+                strength: body.ranged,
+                range: RANGED_ATTACK_RANGE,
+            },
+        );
+    }
+    if body.armor > 0 {
+        armor.insert(entity_id, ArmorComponent { armor: body.armor });
+    }
+    if body.heal > 0 {
+        heal.insert(entity_id, HealComponent { amount: body.heal });
+    }
 
     positions.insert(entity_id, PositionComponent(pos));
 
@@ -117,8 +219,12 @@ type InitResourceMuts = (
 
 type InitResourceConst<'a> = ();
 
-pub fn init_resource_energy(
+/// Initialize a resource deposit of the given type
+///
+/// Panics if `ty` is `Resource::Empty`
+pub fn init_resource(
     id: EntityId,
+    ty: Resource,
     room: Room,
     pos: WorldPosition,
     (
@@ -130,15 +236,17 @@ pub fn init_resource_energy(
     ): InitResourceMuts,
     (): InitResourceConst,
 ) {
-    resources_table.insert(id, ResourceComponent(Resource::Energy));
+    let rule = crate::systems::mineral_system::deposit_rule(ty)
+        .expect("resource deposits can not be empty");
+    resources_table.insert(id, ResourceComponent(ty));
     energy_table.insert(
         id,
         EnergyComponent {
-            energy: 100,
-            energy_max: 100,
+            energy: rule.amount,
+            energy_max: rule.amount,
         },
     );
-    respawn_timer.insert(id, RespawnTimer(2));
+    respawn_timer.insert(id, RespawnTimer(rule.respawn_time));
 
     positions_table.insert(id, PositionComponent(pos));
     entities_by_pos
@@ -1,9 +1,10 @@
 use std::convert::Infallible;
 
+use rand::{RngCore, SeedableRng};
 use tracing::debug;
 
 use crate::{
-    components::EntityScript,
+    components::{EntityScript, WorldRng},
     intents,
     map_generation::room::RoomGenerationParams,
     map_generation::MapGenError,
@@ -15,6 +16,8 @@ use crate::{
 };
 
 pub use crate::components::game_config::GameConfig;
+pub use crate::intents::BotIntents;
+pub use crate::systems::{script_execution::RunResult, SystemDurations};
 
 /// The simplest executor.
 ///
@@ -22,10 +25,7 @@ pub use crate::components::game_config::GameConfig;
 pub struct SimpleExecutor;
 
 impl SimpleExecutor {
-    pub async fn forward_bots(
-        &self,
-        world: &World,
-    ) -> Result<Vec<intents::BotIntents>, Infallible> {
+    pub async fn forward_bots(&self, world: &World) -> Result<RunResult, Infallible> {
         profile!("bots-forward");
 
         let tick = world.time();
@@ -39,51 +39,73 @@ impl SimpleExecutor {
             scripts_table.iter().map(|(id, x)| (id, *x)).collect();
 
         debug!("Executing scripts");
-        let intents = execute_scripts(executions.as_slice(), world).expect("script execution");
+        let result = execute_scripts(executions.as_slice(), world).expect("script execution");
         debug!("Executing scripts Done");
         debug!("Done");
-        Ok(intents)
+        Ok(result)
     }
 
     pub async fn apply_intents(
         &mut self,
         world: &mut World,
         intents: Vec<intents::BotIntents>,
-    ) -> Result<(), Infallible> {
-        profile!("apply-intents");
-
+    ) -> Result<SystemDurations, Infallible> {
         let tick = world.time();
-        let s = tracing::error_span!("apply-intents", tick = tick);
-        let _e = s.enter();
+        let durations;
+        {
+            profile!("apply-intents");
 
-        debug!("Got {} intents", intents.len());
-        intents::move_into_storage(world, intents);
+            let s = tracing::error_span!("apply-intents", tick = tick);
+            let _e = s.enter();
 
-        debug!("Executing systems update");
-        execute_world_update(world);
+            debug!("Got {} intents", intents.len());
+            intents::move_into_storage(world, intents);
 
-        debug!("Executing post-processing");
-        world.post_process();
+            debug!("Executing systems update");
+            durations = execute_world_update(world);
 
-        debug!("Done");
+            debug!("Executing post-processing");
+            world.post_process();
+
+            debug!("Done");
+        }
+        // the tick is done, collect its timings
+        #[cfg(feature = "profile")]
+        crate::profile::end_tick(tick);
 
-        Ok(())
+        Ok(durations)
     }
 
-    pub async fn initialize(&mut self, config: GameConfig) -> World {
+    /// If `config.seed` is set the resulting world is deterministic.
+    pub async fn initialize(&mut self, mut config: GameConfig) -> World {
         let mut world = World::new();
 
-        execute_map_generation(&mut world, &config)
+        let mut rng = config
+            .seed
+            .map(WorldRng::seed_from_u64)
+            .unwrap_or_else(WorldRng::from_entropy);
+        if config.queen_tag.is_empty() {
+            config.queen_tag = rng.gen_uuid().to_string();
+        }
+
+        let mut map_seed = [0; 32];
+        rng.fill_bytes(&mut map_seed);
+        execute_map_generation(&mut world, &config, map_seed)
             .await
             .expect("Failed to generate world map");
 
         world.config.game_config.value = Some(config);
+        world.resources.rng.value = Some(rng);
 
         world
     }
 }
 
-async fn execute_map_generation(world: &mut World, config: &GameConfig) -> Result<(), MapGenError> {
+async fn execute_map_generation(
+    world: &mut World,
+    config: &GameConfig,
+    seed: [u8; 32],
+) -> Result<(), MapGenError> {
     let world_radius = config.world_radius;
     let room_radius = config.room_radius;
     assert!(room_radius > 6);
@@ -106,7 +128,7 @@ async fn execute_map_generation(world: &mut World, config: &GameConfig) -> Resul
     generate_full_map(
         &params,
         &room_params,
-        None,
+        Some(seed),
         FromWorldMut::from_world_mut(world),
     )
     .await?;
@@ -8,9 +8,15 @@ use uuid::Uuid;
 pub fn init_world_entities(storage: &mut World, n_fake_users: usize) {
     debug!("initializing world");
 
-    let mut rng = rand::thread_rng();
-
-    let mining_script_id = ScriptId(Uuid::new_v4());
+    // take the rng out of the world for the duration of the initialization, so we can mutate
+    // the world while drawing from it
+    let mut rng = storage
+        .unsafe_view::<EmptyKey, WorldRng>()
+        .value
+        .take()
+        .expect("World should be initialized with a WorldRng");
+
+    let mining_script_id = ScriptId(rng.gen_uuid());
     let script: CaoIr = serde_yaml::from_str(include_str!("./programs/mining_program.yaml"))
         .expect("deserialize example program");
     debug!("compiling default program");
@@ -64,31 +70,36 @@ pub fn init_world_entities(storage: &mut World, n_fake_users: usize) {
         taken_rooms.push(room);
 
         trace!("initializing room #{} in room {:?}", i, room);
-        let user_id = Uuid::new_v4();
+        let user_id = rng.gen_uuid();
         init_spawn(&bounds, spawnid, user_id, Room(room), &mut rng, storage);
         trace!("spawning entities");
         storage
             .unsafe_view::<UserId, EntityScript>()
             .insert(UserId(user_id), EntityScript(mining_script_id));
-        let id = storage.insert_entity();
-        let pos = uncontested_pos(
-            Room(room),
-            &bounds,
-            &*storage.view::<WorldPosition, EntityComponent>(),
-            &*storage.view::<WorldPosition, TerrainComponent>(),
-            &mut rng,
-        );
-
-        crate::entity_archetypes::init_resource_energy(
-            id,
-            Room(room),
-            pos,
-            FromWorldMut::from_world_mut(storage),
-            FromWorld::from_world(storage),
-        );
+        for ty in Resource::MINABLE.iter().copied() {
+            let id = storage.insert_entity();
+            let pos = uncontested_pos(
+                Room(room),
+                &bounds,
+                &*storage.view::<WorldPosition, EntityComponent>(),
+                &storage.view::<WorldPosition, TerrainComponent>(),
+                &mut rng,
+            );
+
+            crate::entity_archetypes::init_resource(
+                id,
+                ty,
+                Room(room),
+                pos,
+                FromWorldMut::from_world_mut(storage),
+                (),
+            );
+        }
         trace!("initializing room #{} done", i);
     }
 
+    storage.unsafe_view::<EmptyKey, WorldRng>().value = Some(rng);
+
     debug!("init done");
 }
 
@@ -177,4 +188,42 @@ mod tests {
         // smoke test: can the game be even initialized?
         init_world_entities(&mut world, 12);
     }
+
+    #[test]
+    fn seeded_init_is_deterministic() {
+        let init = || {
+            let mut world = futures_lite::future::block_on(SimpleExecutor.initialize(GameConfig {
+                world_radius: 2,
+                room_radius: 10,
+                seed: Some(0xcaf3),
+                ..Default::default()
+            }));
+            init_world_entities(&mut world, 4);
+            world
+        };
+
+        let a = init();
+        let b = init();
+
+        assert_eq!(a.queen_tag(), b.queen_tag());
+        assert_eq!(
+            a.view::<EmptyKey, WorldRng>().value,
+            b.view::<EmptyKey, WorldRng>().value
+        );
+        let users_a: Vec<_> = a.list_users().collect();
+        let users_b: Vec<_> = b.list_users().collect();
+        assert_eq!(users_a, users_b);
+
+        let positions_a: Vec<_> = a
+            .view::<EntityId, PositionComponent>()
+            .iter()
+            .map(|(id, pos)| (id, *pos))
+            .collect();
+        let positions_b: Vec<_> = b
+            .view::<EntityId, PositionComponent>()
+            .iter()
+            .map(|(id, pos)| (id, *pos))
+            .collect();
+        assert_eq!(positions_a, positions_b);
+    }
 }
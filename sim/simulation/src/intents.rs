@@ -1,22 +1,38 @@
 //! Actions, world updates the clients _intend_ to execute.
 //!
 mod attack_intent;
+mod build_intent;
+mod cpu_intent;
 mod dropoff_intent;
+mod energy_transfer_intent;
+mod heal_intent;
 mod log_intent;
+mod memory_intent;
+mod message_intent;
 mod mine_intent;
 mod move_intent;
 mod pathcache_intent;
+mod repair_intent;
 mod spawn_intent;
+mod withdraw_intent;
 
 pub use self::attack_intent::*;
+pub use self::build_intent::*;
+pub use self::cpu_intent::*;
 pub use self::dropoff_intent::*;
+pub use self::energy_transfer_intent::*;
+pub use self::heal_intent::*;
 pub use self::log_intent::*;
+pub use self::memory_intent::*;
+pub use self::message_intent::*;
 pub use self::mine_intent::*;
 pub use self::move_intent::*;
 pub use self::pathcache_intent::*;
+pub use self::repair_intent::*;
 pub use self::spawn_intent::*;
+pub use self::withdraw_intent::*;
 
-use crate::components::ScriptHistoryEntry;
+use crate::components::{ScriptError, ScriptHistoryEntry};
 use crate::indices::{EmptyKey, EntityId};
 use crate::prelude::World;
 use serde::{Deserialize, Serialize};
@@ -99,12 +115,24 @@ macro_rules! intents {
 intents!(
     move_intent: MoveIntent,
     spawn_intent: SpawnIntent,
+    continuous_spawn_intent: ContinuousSpawnIntent,
+    spawn_script_intent: SpawnScriptIntent,
     mine_intent: MineIntent,
     dropoff_intent: DropoffIntent,
+    withdraw_intent: WithdrawIntent,
+    energy_transfer_intent: EnergyTransferIntent,
     log_intent: LogIntent,
     update_path_cache_intent: CachePathIntent,
     mut_path_cache_intent: MutPathCacheIntent,
     script_history_intent: ScriptHistoryEntry,
+    script_error_intent: ScriptError,
+    cpu_usage_intent: CpuUsageIntent,
     melee_attack_intent: MeleeIntent,
+    ranged_attack_intent: RangedIntent,
+    heal_intent: HealIntent,
+    repair_intent: RepairIntent,
+    build_intent: BuildIntent,
     say_intent: SayIntent,
+    memory_intent: MemoryIntent,
+    messages_intent: MessagesIntent,
 );
@@ -1,4 +1,6 @@
-use crate::components::{HpComponent, MeleeAttackComponent, OwnedEntity, PositionComponent};
+use crate::components::{
+    HpComponent, MeleeAttackComponent, OwnedEntity, PositionComponent, RangedAttackComponent,
+};
 use crate::indices::{EntityId, UserId};
 use crate::scripting_api::OperationResult;
 use crate::storage::views::View;
@@ -11,6 +13,12 @@ pub struct MeleeIntent {
     pub defender: EntityId,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RangedIntent {
+    pub attacker: EntityId,
+    pub defender: EntityId,
+}
+
 type CheckInput<'a> = (
     View<'a, EntityId, OwnedEntity>,
     View<'a, EntityId, PositionComponent>,
@@ -77,3 +85,72 @@ pub fn check_melee_intent(
     }
     OperationResult::Ok
 }
+
+type CheckRangedInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, EntityId, RangedAttackComponent>,
+    View<'a, EntityId, HpComponent>,
+);
+
+/// `attacker` must be owned by the user.
+/// `attacker` must have `RangedAttackComponent`
+/// `defender` must have `HpComponent` and can not be the `attacker`
+/// `defender` must be within the range of the attacker
+pub fn check_ranged_intent(
+    intent: &RangedIntent,
+    user_id: UserId,
+    (owner_table, pos_table, ranged_table, hp_table): CheckRangedInput,
+) -> OperationResult {
+    let s = tracing::span!(
+        tracing::Level::INFO,
+        "check_ranged_intent",
+        attacker = intent.attacker.to_string().as_str(),
+        defender = intent.defender.to_string().as_str()
+    );
+    let _e = s.enter();
+
+    trace!("check_ranged_intent");
+
+    if owner_table
+        .get(intent.attacker)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    let ranged = match ranged_table.get(intent.attacker) {
+        Some(x) => x,
+        None => {
+            debug!("attacker has no RangedAttackComponent");
+            return OperationResult::InvalidInput;
+        }
+    };
+    if intent.attacker == intent.defender || !hp_table.contains(intent.defender) {
+        debug!("defender is the attacker or has no HpComponent");
+        return OperationResult::InvalidTarget;
+    }
+    let attack_pos = match pos_table.get(intent.attacker) {
+        Some(x) => x,
+        None => {
+            debug!("attacker has no PositionComponent");
+            return OperationResult::InvalidInput;
+        }
+    };
+    let defend_pos = match pos_table.get(intent.defender) {
+        Some(x) => x,
+        None => {
+            debug!("defender has no PositionComponent");
+            return OperationResult::InvalidTarget;
+        }
+    };
+    if attack_pos.0.room != defend_pos.0.room {
+        debug!("Attacker and defender are not in the same room");
+        return OperationResult::InvalidTarget;
+    }
+    if attack_pos.0.pos.hex_distance(defend_pos.0.pos) > ranged.range as u32 {
+        debug!("Attacker is out of range");
+        return OperationResult::NotInRange;
+    }
+    OperationResult::Ok
+}
@@ -0,0 +1,66 @@
+use crate::components::game_config::GameConfig;
+use crate::components::{
+    EntityComponent, OwnedEntity, PositionComponent, StructureType, TerrainComponent,
+};
+use crate::indices::{ConfigKey, EntityId, UserId, WorldPosition};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::{UnwrapView, View};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Place a construction site of a structure
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildIntent {
+    pub builder: EntityId,
+    pub owner_id: UserId,
+    pub pos: WorldPosition,
+    pub ty: StructureType,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, WorldPosition, TerrainComponent>,
+    View<'a, WorldPosition, EntityComponent>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+/// A valid build intent has the following characteristics:
+/// - the builder is owned by the user
+/// - the structure can be built by scripts
+/// - the position is in the room of the builder
+/// - the position is walkable and free
+pub fn check_build_intent(
+    intent: &BuildIntent,
+    user_id: UserId,
+    (owners, positions, terrain, entities, config): CheckInput,
+) -> OperationResult {
+    if owners
+        .get(intent.builder)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if config.construction_cost.cost(intent.ty).is_none() {
+        debug!("{:?} can not be built", intent.ty);
+        return OperationResult::InvalidInput;
+    }
+    match positions.get(intent.builder) {
+        Some(pos) if pos.0.room == intent.pos.room => {}
+        Some(_) => return OperationResult::NotInRange,
+        None => {
+            debug!("builder has no position");
+            return OperationResult::InvalidInput;
+        }
+    }
+    let walkable = terrain
+        .get(intent.pos)
+        .map(|TerrainComponent(t)| t.is_walkable())
+        .unwrap_or(false);
+    if !walkable || entities.get(intent.pos).is_some() {
+        debug!("position {:?} is not walkable or taken", intent.pos);
+        return OperationResult::InvalidTarget;
+    }
+    OperationResult::Ok
+}
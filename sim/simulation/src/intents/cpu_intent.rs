@@ -0,0 +1,9 @@
+use crate::indices::UserId;
+use serde::{Deserialize, Serialize};
+
+/// Instructions reserved by the scripts of a user in a tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CpuUsageIntent {
+    pub user_id: UserId,
+    pub instructions: u64,
+}
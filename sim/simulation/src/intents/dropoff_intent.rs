@@ -1,5 +1,6 @@
 use crate::components::{
-    Bot, CarryComponent, EnergyComponent, OwnedEntity, PositionComponent, Resource,
+    Bot, CarryComponent, EnergyComponent, InventoryComponent, OwnedEntity, PositionComponent,
+    Resource,
 };
 use crate::indices::{EntityId, UserId};
 use crate::scripting_api::OperationResult;
@@ -24,17 +25,19 @@ type CheckInput<'a> = (
     View<'a, EntityId, PositionComponent>,
     View<'a, EntityId, CarryComponent>,
     View<'a, EntityId, EnergyComponent>,
+    View<'a, EntityId, InventoryComponent>,
 );
 
 /// A valid dropoff intent has the following characteristics:
 /// - the bot is owned by the user
 /// - the bot is carrying resource of type `ty`
-/// - the target is not full
+/// - the target can store resources of type `ty` and is not full. Inventories store any
+///   resource, energy stores only energy
 /// - the target is within dropoff range
 pub fn check_dropoff_intent(
     intent: &DropoffIntent,
     userid: UserId,
-    (bots, owners, positions, carry, energy): CheckInput,
+    (bots, owners, positions, carry, energy, inventory): CheckInput,
 ) -> OperationResult {
     let id = intent.bot;
     match bots.get(id) {
@@ -47,11 +50,29 @@ pub fn check_dropoff_intent(
         None => return OperationResult::InvalidInput,
     };
 
-    if carry.get(id).map(|carry| carry.carry == 0).unwrap_or(true) {
+    if carry
+        .get(id)
+        .map(|carry| carry.resources.get(intent.ty) == 0)
+        .unwrap_or(true)
+    {
         return OperationResult::Empty;
     }
 
     let target = intent.structure;
+    let full = match (inventory.get(target), energy.get(target)) {
+        (Some(inventory), _) => inventory.free() == 0,
+        // energy stores only accept energy
+        (None, Some(_)) if intent.ty != Resource::Energy => {
+            debug!("Target can not store {:?}", intent.ty);
+            return OperationResult::InvalidTarget;
+        }
+        (None, Some(energy)) => energy.energy >= energy.energy_max,
+        (None, None) => {
+            debug!("Target has no energy component or inventory {:?}", intent);
+            return OperationResult::InvalidInput;
+        }
+    };
+
     let nearby = positions.get(id).and_then(|botpos| {
         positions.get(target).map(|targetpos| {
             targetpos.0.room == botpos.0.room
@@ -64,18 +85,7 @@ pub fn check_dropoff_intent(
             OperationResult::InvalidInput
         }
         Some(false) => OperationResult::NotInRange,
-        Some(true) => {
-            let capacity = energy.get(target);
-            if capacity.is_none() {
-                debug!("Target has no energy component {:?}", intent);
-                return OperationResult::InvalidInput;
-            }
-            let capacity = capacity.unwrap();
-            if capacity.energy < capacity.energy_max {
-                OperationResult::Ok
-            } else {
-                OperationResult::Full
-            }
-        }
+        Some(true) if full => OperationResult::Full,
+        Some(true) => OperationResult::Ok,
     }
 }
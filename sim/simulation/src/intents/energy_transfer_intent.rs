@@ -0,0 +1,84 @@
+use crate::components::{
+    CarryComponent, EnergyComponent, OwnedEntity, PositionComponent, Structure,
+};
+use crate::indices::{EntityId, UserId};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::View;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+pub const ENERGY_TRANSFER_RANGE: u32 = 1;
+
+/// Move as much energy from a structure to the target as the target can hold
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyTransferIntent {
+    pub structure: EntityId,
+    pub target: EntityId,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, Structure>,
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, EntityId, EnergyComponent>,
+    View<'a, EntityId, CarryComponent>,
+);
+
+/// A valid energy transfer intent has the following characteristics:
+/// - the structure is owned by the user
+/// - the structure has energy
+/// - the target stores energy or carries resources and is not full
+/// - the target is within transfer range
+pub fn check_energy_transfer_intent(
+    intent: &EnergyTransferIntent,
+    user_id: UserId,
+    (structures, owners, positions, energy, carry): CheckInput,
+) -> OperationResult {
+    let id = intent.structure;
+    if !structures.contains(&id) {
+        debug!("{:?} is not a structure", id);
+        return OperationResult::InvalidInput;
+    }
+    if owners
+        .get(id)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if energy.get(id).map(|e| e.energy == 0).unwrap_or(true) {
+        return OperationResult::Empty;
+    }
+    if id == intent.target {
+        return OperationResult::InvalidTarget;
+    }
+
+    let target = intent.target;
+    let free = match (energy.get(target), carry.get(target)) {
+        (Some(e), _) => e.energy_max.saturating_sub(e.energy),
+        (None, Some(c)) => c.free(),
+        (None, None) => {
+            debug!("Target can not hold energy {:?}", intent);
+            return OperationResult::InvalidTarget;
+        }
+    };
+
+    let nearby = positions.get(id).and_then(|pos| {
+        positions.get(target).map(|targetpos| {
+            targetpos.0.room == pos.0.room
+                && targetpos.0.pos.hex_distance(pos.0.pos) <= ENERGY_TRANSFER_RANGE
+        })
+    });
+    match nearby {
+        None => {
+            debug!(
+                "Structure or target has no position components {:?}",
+                intent
+            );
+            OperationResult::InvalidInput
+        }
+        Some(false) => OperationResult::NotInRange,
+        Some(true) if free == 0 => OperationResult::Full,
+        Some(true) => OperationResult::Ok,
+    }
+}
@@ -0,0 +1,70 @@
+use crate::components::{Bot, HealComponent, HpComponent, OwnedEntity, PositionComponent};
+use crate::indices::{EntityId, UserId};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::View;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+pub const HEAL_RANGE: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealIntent {
+    pub healer: EntityId,
+    pub target: EntityId,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, EntityId, HealComponent>,
+    View<'a, EntityId, Bot>,
+    View<'a, EntityId, HpComponent>,
+);
+
+/// A valid heal intent has the following characteristics:
+/// - the healer is owned by the user and has a `HealComponent`
+/// - the target is a bot that is not at full hp
+/// - the target is within heal range
+pub fn check_heal_intent(
+    intent: &HealIntent,
+    user_id: UserId,
+    (owners, positions, heal, bots, hp): CheckInput,
+) -> OperationResult {
+    if owners
+        .get(intent.healer)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if !heal.contains(intent.healer) {
+        debug!("healer has no HealComponent");
+        return OperationResult::InvalidInput;
+    }
+    if !bots.contains(&intent.target) {
+        debug!("target is not a bot");
+        return OperationResult::InvalidTarget;
+    }
+    let target_hp = match hp.get(intent.target) {
+        Some(hp) => hp,
+        None => {
+            debug!("target has no HpComponent");
+            return OperationResult::InvalidTarget;
+        }
+    };
+
+    let nearby = positions.get(intent.healer).and_then(|pos| {
+        positions.get(intent.target).map(|targetpos| {
+            targetpos.0.room == pos.0.room && targetpos.0.pos.hex_distance(pos.0.pos) <= HEAL_RANGE
+        })
+    });
+    match nearby {
+        None => {
+            debug!("Healer or target has no position components {:?}", intent);
+            OperationResult::InvalidInput
+        }
+        Some(false) => OperationResult::NotInRange,
+        Some(true) if target_hp.hp >= target_hp.hp_max => OperationResult::Full,
+        Some(true) => OperationResult::Ok,
+    }
+}
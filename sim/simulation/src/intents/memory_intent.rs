@@ -0,0 +1,14 @@
+use crate::components::{MemoryValue, ScriptMemory};
+use crate::indices::{EntityId, UserId};
+use serde::{Deserialize, Serialize};
+
+/// Changes a script made to its memories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryIntent {
+    pub entity_id: EntityId,
+    /// The new memory of the entity, if it was changed
+    pub entity_memory: Option<ScriptMemory>,
+    pub user_id: Option<UserId>,
+    /// Keys of the user's memory set by the script, in order. `None` removes the key
+    pub user_memory_changes: Vec<(String, Option<MemoryValue>)>,
+}
@@ -0,0 +1,28 @@
+use crate::components::MemoryValue;
+use crate::indices::{EntityId, UserId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageTarget {
+    /// A named channel of the sender's owner
+    Channel(String),
+    Entity(EntityId),
+}
+
+impl MessageTarget {
+    /// Number of bytes the target counts towards the message limits
+    pub fn size(&self) -> usize {
+        match self {
+            MessageTarget::Channel(name) => name.len(),
+            MessageTarget::Entity(_) => 8,
+        }
+    }
+}
+
+/// Messages a script sent in a tick, in order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessagesIntent {
+    pub sender: EntityId,
+    pub user_id: UserId,
+    pub messages: Vec<(MessageTarget, MemoryValue)>,
+}
@@ -59,7 +59,7 @@ pub fn check_mine_intent(
 
     match carry_table.get(bot) {
         Some(carry) => {
-            if carry.carry >= carry.carry_max {
+            if carry.free() == 0 {
                 debug!("{} is full", bot);
                 return OperationResult::Full;
             }
@@ -89,7 +89,7 @@ pub fn check_mine_intent(
     }
 
     match resources_table.get(target) {
-        Some(components::ResourceComponent(components::Resource::Energy)) => {
+        Some(components::ResourceComponent(ty)) if *ty != components::Resource::Empty => {
             match energy_table.get(target) {
                 Some(energy) => {
                     if energy.energy > 0 {
@@ -0,0 +1,86 @@
+use crate::components::{
+    Bot, CarryComponent, HpComponent, OwnedEntity, PositionComponent, Resource, Structure,
+};
+use crate::indices::{EntityId, UserId};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::View;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+pub const REPAIR_RANGE: u32 = 1;
+/// Maximum hp restored by a bot in a tick. Every hp costs 1 carried energy
+pub const REPAIR_AMOUNT: u16 = 20;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairIntent {
+    pub bot: EntityId,
+    pub structure: EntityId,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, Bot>,
+    View<'a, EntityId, Structure>,
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, EntityId, CarryComponent>,
+    View<'a, EntityId, HpComponent>,
+);
+
+/// A valid repair intent has the following characteristics:
+/// - the bot is owned by the user
+/// - the bot is carrying energy
+/// - the target is a structure that is not at full hp
+/// - the target is within repair range
+pub fn check_repair_intent(
+    intent: &RepairIntent,
+    user_id: UserId,
+    (bots, structures, owners, positions, carry, hp): CheckInput,
+) -> OperationResult {
+    let id = intent.bot;
+    if !bots.contains(&id) {
+        debug!("{:?} is not a bot", id);
+        return OperationResult::InvalidInput;
+    }
+    if owners
+        .get(id)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if carry
+        .get(id)
+        .map(|carry| carry.resources.get(Resource::Energy) == 0)
+        .unwrap_or(true)
+    {
+        return OperationResult::Empty;
+    }
+    let target = intent.structure;
+    if !structures.contains(&target) {
+        debug!("{:?} is not a structure", target);
+        return OperationResult::InvalidTarget;
+    }
+    let target_hp = match hp.get(target) {
+        Some(hp) => hp,
+        None => {
+            debug!("structure has no HpComponent");
+            return OperationResult::InvalidTarget;
+        }
+    };
+
+    let nearby = positions.get(id).and_then(|botpos| {
+        positions.get(target).map(|targetpos| {
+            targetpos.0.room == botpos.0.room
+                && targetpos.0.pos.hex_distance(botpos.0.pos) <= REPAIR_RANGE
+        })
+    });
+    match nearby {
+        None => {
+            debug!("Bot or target has no position components {:?}", intent);
+            OperationResult::InvalidInput
+        }
+        Some(false) => OperationResult::NotInRange,
+        Some(true) if target_hp.hp >= target_hp.hp_max => OperationResult::Full,
+        Some(true) => OperationResult::Ok,
+    }
+}
@@ -1,8 +1,144 @@
-use crate::indices::{EntityId, UserId};
+use crate::components::game_config::GameConfig;
+use crate::components::{
+    BotBody, CompiledScriptComponent, EnergyComponent, OwnedEntity, SpawnComponent,
+    SpawnQueueComponent,
+};
+use crate::indices::{ConfigKey, EntityId, ScriptId, UserId};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::{UnwrapView, View};
 use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Maximum number of bots waiting in the queue of a spawn
+pub const SPAWN_QUEUE_LIMIT: usize = 20;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SpawnIntent {
     pub spawn_id: EntityId,
     pub owner_id: Option<UserId>,
+    pub body: BotBody,
+}
+
+/// Turn continuous spawning of a spawn on or off
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContinuousSpawnIntent {
+    pub spawn_id: EntityId,
+    pub enabled: bool,
+}
+
+/// Set the script the bots of a spawn run. `None` restores the default script of the owner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpawnScriptIntent {
+    pub spawn_id: EntityId,
+    pub script_id: Option<ScriptId>,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, SpawnQueueComponent>,
+    View<'a, EntityId, EnergyComponent>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+/// A valid spawn intent has the following characteristics:
+/// - the spawn is owned by the user
+/// - the spawn's queue is not full
+/// - the body has hp and a positive decay interval
+/// - the spawn can store enough energy to pay for the body
+pub fn check_spawn_intent(
+    intent: &SpawnIntent,
+    user_id: UserId,
+    (owners, queues, energy, config): CheckInput,
+) -> OperationResult {
+    if owners
+        .get(intent.spawn_id)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    let queue = match queues.get(intent.spawn_id) {
+        Some(q) => q,
+        None => {
+            debug!("structure {:?} is not a spawn", intent.spawn_id);
+            return OperationResult::InvalidTarget;
+        }
+    };
+    if queue.queue.len() >= SPAWN_QUEUE_LIMIT {
+        return OperationResult::Full;
+    }
+    if intent.body.hp == 0 || intent.body.decay_interval == 0 {
+        debug!("invalid body {:?}", intent.body);
+        return OperationResult::InvalidInput;
+    }
+    let cost = config.bot_body_cost.cost(&intent.body);
+    match energy.get(intent.spawn_id) {
+        Some(energy) if cost <= energy.energy_max as u32 => OperationResult::Ok,
+        _ => {
+            debug!(
+                "body {:?} costs more ({}) than the spawn can store",
+                intent.body, cost
+            );
+            OperationResult::InvalidInput
+        }
+    }
+}
+
+type CheckContinuousInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, SpawnComponent>,
+);
+
+/// The spawn must be owned by the user
+pub fn check_continuous_spawn_intent(
+    intent: &ContinuousSpawnIntent,
+    user_id: UserId,
+    (owners, spawns): CheckContinuousInput,
+) -> OperationResult {
+    if owners
+        .get(intent.spawn_id)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if !spawns.contains(intent.spawn_id) {
+        debug!("structure {:?} is not a spawn", intent.spawn_id);
+        return OperationResult::InvalidTarget;
+    }
+    OperationResult::Ok
+}
+
+type CheckSpawnScriptInput<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, SpawnComponent>,
+    View<'a, ScriptId, CompiledScriptComponent>,
+);
+
+/// A valid spawn script intent has the following characteristics:
+/// - the spawn is owned by the user
+/// - the script, if set, exists
+pub fn check_spawn_script_intent(
+    intent: &SpawnScriptIntent,
+    user_id: UserId,
+    (owners, spawns, scripts): CheckSpawnScriptInput,
+) -> OperationResult {
+    if owners
+        .get(intent.spawn_id)
+        .map(|o| o.owner_id != user_id)
+        .unwrap_or(true)
+    {
+        return OperationResult::NotOwner;
+    }
+    if !spawns.contains(intent.spawn_id) {
+        debug!("structure {:?} is not a spawn", intent.spawn_id);
+        return OperationResult::InvalidTarget;
+    }
+    match intent.script_id {
+        Some(script_id) if !scripts.contains(script_id) => {
+            debug!("script {:?} does not exist", script_id);
+            OperationResult::InvalidInput
+        }
+        _ => OperationResult::Ok,
+    }
 }
@@ -0,0 +1,87 @@
+use crate::components::{
+    Bot, CarryComponent, InventoryComponent, OwnedEntity, PositionComponent, Resource,
+};
+use crate::indices::{EntityId, UserId};
+use crate::scripting_api::OperationResult;
+use crate::storage::views::View;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+pub const WITHDRAW_RANGE: u32 = 1;
+
+/// Take resources out of the inventory of a structure.
+/// The inverse of [DropoffIntent](super::DropoffIntent)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WithdrawIntent {
+    pub bot: EntityId,
+    pub structure: EntityId,
+    pub amount: u16,
+    pub ty: Resource,
+}
+
+type CheckInput<'a> = (
+    View<'a, EntityId, Bot>,
+    View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, PositionComponent>,
+    View<'a, EntityId, CarryComponent>,
+    View<'a, EntityId, InventoryComponent>,
+);
+
+/// A valid withdraw intent has the following characteristics:
+/// - both the bot and the target are owned by the user
+/// - the target has an inventory that holds resource of type `ty`
+/// - the bot is not full
+/// - the target is within withdraw range
+pub fn check_withdraw_intent(
+    intent: &WithdrawIntent,
+    user_id: UserId,
+    (bots, owners, positions, carry, inventory): CheckInput,
+) -> OperationResult {
+    let id = intent.bot;
+    if !bots.contains(&id) {
+        debug!("{:?} is not a bot", id);
+        return OperationResult::InvalidInput;
+    }
+    let is_owned = |id| {
+        owners
+            .get(id)
+            .map(|o| o.owner_id == user_id)
+            .unwrap_or(false)
+    };
+    if !is_owned(id) {
+        return OperationResult::NotOwner;
+    }
+
+    let target = intent.structure;
+    let stored = match inventory.get(target) {
+        Some(inventory) => inventory.resources.get(intent.ty),
+        None => {
+            debug!("Target has no inventory {:?}", intent);
+            return OperationResult::InvalidTarget;
+        }
+    };
+    if !is_owned(target) {
+        return OperationResult::NotOwner;
+    }
+    if stored == 0 {
+        return OperationResult::Empty;
+    }
+    if carry.get(id).map(|c| c.free() == 0).unwrap_or(true) {
+        return OperationResult::Full;
+    }
+
+    let nearby = positions.get(id).and_then(|botpos| {
+        positions.get(target).map(|targetpos| {
+            targetpos.0.room == botpos.0.room
+                && targetpos.0.pos.hex_distance(botpos.0.pos) <= WITHDRAW_RANGE
+        })
+    });
+    match nearby {
+        None => {
+            debug!("Bot or target has no position components {:?}", intent);
+            OperationResult::InvalidInput
+        }
+        Some(false) => OperationResult::NotInRange,
+        Some(true) => OperationResult::Ok,
+    }
+}
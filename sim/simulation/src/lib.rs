@@ -8,6 +8,8 @@ pub mod map_generation;
 pub mod noise;
 pub mod pathfinding;
 pub mod prelude;
+#[cfg(feature = "profile")]
+pub mod profile;
 pub mod scripting_api;
 pub mod storage;
 pub mod tables;
@@ -304,13 +304,14 @@ fn connect_chunks(
     'chunks: for (last_index, chunk) in chunks[1..].iter().enumerate() {
         let avg: Axial =
             chunk.iter().copied().fold(Axial::default(), |a, b| a + b) / chunk.len() as i32;
+        // break ties by position, the iteration order of the chunks is not deterministic
         let closest = *chunks[last_index]
             .iter()
-            .min_by_key(|p| p.hex_distance(avg))
+            .min_by_key(|p| (p.hex_distance(avg), p.q, p.r))
             .unwrap();
         let mut current = *chunk
             .iter()
-            .min_by_key(|p| p.hex_distance(closest))
+            .min_by_key(|p| (p.hex_distance(closest), p.q, p.r))
             .unwrap();
 
         let get_next_step = |current| {
@@ -0,0 +1,213 @@
+//! Backend of the [profile](crate::profile) macro. Only available with the `profile` feature.
+//!
+//! Timings are aggregated per label, and collected at the end of every tick by the executor.
+//! Collected ticks are appended to a CSV file (`profile.csv` by default, override via the
+//! `CAO_PROFILE_CSV` environment variable), and the last one can be read via [last_tick].
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelStats {
+    pub calls: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl LabelStats {
+    fn new(duration: Duration) -> Self {
+        Self {
+            calls: 1,
+            total: duration,
+            min: duration,
+            max: duration,
+        }
+    }
+
+    fn push(&mut self, duration: Duration) {
+        self.calls += 1;
+        self.total += duration;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TickProfile {
+    pub tick: u64,
+    pub labels: BTreeMap<&'static str, LabelStats>,
+}
+
+#[derive(Debug)]
+enum CsvOutput {
+    /// Open the default output on the first flush
+    Default,
+    File(BufWriter<fs::File>),
+    Disabled,
+}
+
+#[derive(Debug)]
+struct Profiler {
+    current: BTreeMap<&'static str, LabelStats>,
+    last: Option<TickProfile>,
+    csv: CsvOutput,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            current: BTreeMap::new(),
+            last: None,
+            csv: CsvOutput::Default,
+        }
+    }
+}
+
+static PROFILER: Mutex<Option<Profiler>> = Mutex::new(None);
+
+fn with_profiler<R>(f: impl FnOnce(&mut Profiler) -> R) -> R {
+    // a panic while holding the lock can not leave the profiler in an invalid state
+    let mut profiler = PROFILER.lock().unwrap_or_else(|err| err.into_inner());
+    f(profiler.get_or_insert_with(Profiler::default))
+}
+
+/// Records the time between its creation and drop
+pub struct Timer {
+    label: &'static str,
+    start: Instant,
+}
+
+impl Timer {
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        with_profiler(|profiler| profiler.record(self.label, duration));
+    }
+}
+
+/// Close the current tick and flush its timings to the CSV output
+pub fn end_tick(tick: u64) {
+    with_profiler(|profiler| profiler.end_tick(tick));
+}
+
+/// Timings of the last finished tick
+pub fn last_tick() -> Option<TickProfile> {
+    with_profiler(|profiler| profiler.last.clone())
+}
+
+/// Write the timings to `path` instead of the default output. Truncates existing files.
+pub fn set_csv_output(path: &Path) -> io::Result<()> {
+    let out = open_csv(path)?;
+    with_profiler(|profiler| profiler.csv = CsvOutput::File(out));
+    Ok(())
+}
+
+pub fn disable_csv_output() {
+    with_profiler(|profiler| profiler.csv = CsvOutput::Disabled);
+}
+
+fn open_csv(path: &Path) -> io::Result<BufWriter<fs::File>> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "tick,label,calls,total_ns,min_ns,max_ns")?;
+    Ok(out)
+}
+
+impl Profiler {
+    fn record(&mut self, label: &'static str, duration: Duration) {
+        self.current
+            .entry(label)
+            .and_modify(|stats| stats.push(duration))
+            .or_insert_with(|| LabelStats::new(duration));
+    }
+
+    fn end_tick(&mut self, tick: u64) {
+        let profile = TickProfile {
+            tick,
+            labels: std::mem::take(&mut self.current),
+        };
+        if let Err(err) = self.write_csv(&profile) {
+            error!("Failed to write profile, disabling CSV output: {}", err);
+            self.csv = CsvOutput::Disabled;
+        }
+        self.last = Some(profile);
+    }
+
+    fn write_csv(&mut self, profile: &TickProfile) -> io::Result<()> {
+        if let CsvOutput::Default = self.csv {
+            let path = std::env::var("CAO_PROFILE_CSV").unwrap_or_else(|_| "profile.csv".into());
+            self.csv = CsvOutput::File(open_csv(Path::new(&path))?);
+        }
+        let out = match &mut self.csv {
+            CsvOutput::File(out) => out,
+            _ => return Ok(()),
+        };
+        for (label, stats) in profile.labels.iter() {
+            writeln!(
+                out,
+                "{},\"{}\",{},{},{},{}",
+                profile.tick,
+                label,
+                stats.calls,
+                stats.total.as_nanos(),
+                stats.min.as_nanos(),
+                stats.max.as_nanos()
+            )?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregates_per_tick() {
+        let mut profiler = Profiler {
+            csv: CsvOutput::Disabled,
+            ..Default::default()
+        };
+
+        profiler.record("a", Duration::from_millis(3));
+        profiler.record("a", Duration::from_millis(1));
+        profiler.record("b", Duration::from_millis(2));
+        profiler.end_tick(1);
+
+        let last = profiler.last.as_ref().unwrap();
+        assert_eq!(last.tick, 1);
+        assert_eq!(
+            last.labels["a"],
+            LabelStats {
+                calls: 2,
+                total: Duration::from_millis(4),
+                min: Duration::from_millis(1),
+                max: Duration::from_millis(3),
+            }
+        );
+        assert_eq!(last.labels["b"].calls, 1);
+
+        profiler.record("b", Duration::from_millis(2));
+        profiler.end_tick(2);
+
+        let last = profiler.last.as_ref().unwrap();
+        assert_eq!(last.tick, 2);
+        assert!(!last.labels.contains_key("a"));
+        assert_eq!(last.labels["b"].calls, 1);
+    }
+}
@@ -1,12 +1,21 @@
 //! Methods that are exported to the Cao-lang clients
 //!
-//! Methods that may fail return an OperationResult
+//! Actions return an `ActionResult` Object `{ok, code, reason}`, where `code` is the
+//! [OperationResult](OperationResult) of the action. Queries return a `QueryResult` Object
+//! `{found, value}`. See [ImportKind](ImportKind).
 //!
 #[cfg(test)]
 mod tests;
 
 pub mod bots;
+pub mod construction_api;
+pub mod entity_api;
 pub mod find_api;
+pub mod map_api;
+pub mod memory_api;
+pub mod message_api;
+pub mod spawn_api;
+pub mod structure_api;
 use crate::geometry::Axial;
 use crate::indices::{EntityId, WorldPosition};
 use crate::profile;
@@ -65,6 +74,84 @@ impl From<OperationResult> for Value {
     }
 }
 
+impl OperationResult {
+    /// Short, human readable description of the result
+    pub fn reason(self) -> &'static str {
+        match self {
+            OperationResult::Ok => "ok",
+            OperationResult::NotOwner => "the entity is not owned by the user",
+            OperationResult::InvalidInput => "invalid input",
+            OperationResult::OperationFailed => "the operation failed",
+            OperationResult::NotInRange => "the target is not in range",
+            OperationResult::InvalidTarget => "invalid target",
+            OperationResult::Empty => "the entity is empty",
+            OperationResult::Full => "the target is full",
+            OperationResult::PathNotFound => "no path was found",
+        }
+    }
+}
+
+/// Determines the value an import pushes
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImportKind {
+    /// Pushes its outputs as they are
+    Function,
+    /// Pushes an `ActionResult` Object with the fields
+    /// - `ok`: 1 if the action succeeded, 0 otherwise
+    /// - `code`: the [OperationResult](OperationResult) of the action
+    /// - `reason`: the description of the result
+    Action,
+    /// Pushes a `QueryResult` Object with the fields
+    /// - `found`: 1 if the query found a value, 0 otherwise
+    /// - `value`: the result of the query or `Nil`
+    Query,
+}
+
+impl ImportKind {
+    /// Wrap the implementation of an import so it pushes the result convention of the kind.
+    ///
+    /// Actions are implemented by functions pushing an `OperationResult`, queries by
+    /// functions pushing `Nil` if nothing was found.
+    pub fn wrap<F>(self, fo: F) -> Box<dyn VmFunction<ScriptExecutionData>>
+    where
+        F: VmFunction<ScriptExecutionData> + 'static,
+    {
+        match self {
+            ImportKind::Function => Box::new(fo),
+            ImportKind::Action => Box::new(move |vm: &mut Vm<ScriptExecutionData>| {
+                fo.call(vm)?;
+                let res = vm.stack_pop();
+                let res = OperationResult::try_from(res).map_err(|value| {
+                    error!("Action pushed {:?} instead of an OperationResult", value);
+                    ExecutionError::TaskFailure("Internal Error".to_string())
+                })?;
+                let reason = Value::String(init_string(vm, res.reason())?);
+                let res = make_object(
+                    vm,
+                    &[
+                        ("ok", Value::Integer((res == OperationResult::Ok) as i64)),
+                        ("code", res.into()),
+                        ("reason", reason),
+                    ],
+                )?;
+                vm.stack_push(res)?;
+                Ok(())
+            }),
+            ImportKind::Query => Box::new(move |vm: &mut Vm<ScriptExecutionData>| {
+                fo.call(vm)?;
+                let value = vm.stack_pop();
+                let found = !matches!(value, Value::Nil);
+                let res = make_object(
+                    vm,
+                    &[("found", Value::Integer(found as i64)), ("value", value)],
+                )?;
+                vm.stack_push(res)?;
+                Ok(())
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Script {
@@ -190,9 +277,21 @@ pub fn say(vm: &mut Vm<ScriptExecutionData>, value: Value) -> Result<(), Executi
     Ok(())
 }
 
+/// Push the cpu budget the user has left in this tick, or `Nil` if the entity has no owner
+pub fn cpu_remaining(vm: &mut Vm<ScriptExecutionData>) -> Result<(), ExecutionError> {
+    profile!("cpu_remaining");
+    let value = match vm.get_aux().cpu_remaining {
+        Some(cpu) => Value::Integer(cpu as i64),
+        None => Value::Nil,
+    };
+    vm.stack_push(value)?;
+    Ok(())
+}
+
 /// Holds data about a function
 pub struct FunctionRow {
     pub desc: SubProgram<'static>,
+    pub kind: ImportKind,
     pub fo: Box<dyn VmFunction<ScriptExecutionData>>,
 }
 
@@ -216,6 +315,39 @@ impl Schema {
         self.imports.iter().map(|fr| fr.desc.name)
     }
 
+    /// Render the reference documentation of the imports as Markdown
+    pub fn docs(&self) -> String {
+        let list = |items: Vec<String>| {
+            if items.is_empty() {
+                "-".to_string()
+            } else {
+                items
+                    .iter()
+                    .map(|item| format!("`{}`", item))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        let mut docs = String::from(
+            "# Scripting API\n\n\
+            Actions return an `ActionResult` Object: `ok` is 1 if the action succeeded, 0 \
+            otherwise, `code` is the `OperationResult` of the action and `reason` describes it.\n\n\
+            Queries return a `QueryResult` Object: `found` is 1 if the query found a value, 0 \
+            otherwise, and `value` holds the value or `Nil`.\n",
+        );
+        for FunctionRow { desc, kind, .. } in self.imports.iter() {
+            docs.push_str(&format!(
+                "\n## {}\n\n{}\n\n- Kind: {:?}\n- Inputs: {}\n- Outputs: {}\n",
+                desc.name,
+                desc.description,
+                kind,
+                list(desc.input.iter().map(|x| x.to_string()).collect()),
+                list(desc.output.iter().map(|x| x.to_string()).collect()),
+            ));
+        }
+        docs
+    }
+
     pub fn execute_imports(self, vm: &mut Vm<ScriptExecutionData>) {
         for fr in self.imports {
             vm.register_function(fr.desc.name, move |vm: &mut Vm<_>| { let _ = &fr; fr.fo.call(vm) });
@@ -240,6 +372,119 @@ pub fn parse_world_pos(point: &FieldTable) -> Result<WorldPosition, ExecutionErr
     })
 }
 
+/// Inverse of [parse_world_pos](parse_world_pos)
+pub fn world_pos_to_object(
+    vm: &mut Vm<ScriptExecutionData>,
+    pos: WorldPosition,
+) -> Result<Value, ExecutionError> {
+    make_object(
+        vm,
+        &[
+            ("rq", Value::Integer(pos.room.q as i64)),
+            ("rr", Value::Integer(pos.room.r as i64)),
+            ("q", Value::Integer(pos.pos.q as i64)),
+            ("r", Value::Integer(pos.pos.r as i64)),
+        ],
+    )
+}
+
+/// Takes a Cao-Lang Object (FieldTable) and reads a room id from the fields `rq` and `rr`.
+///
+/// Objects produced by [world_pos_to_object](world_pos_to_object) are valid rooms as well.
+pub fn parse_room(point: &FieldTable) -> Result<Axial, ExecutionError> {
+    let rq = _get_parse_coordinate(point, "rq")?;
+    let rr = _get_parse_coordinate(point, "rr")?;
+    Ok(Axial::new(rq, rr))
+}
+
+/// Inverse of [parse_room](parse_room)
+pub fn room_to_object(
+    vm: &mut Vm<ScriptExecutionData>,
+    room: Axial,
+) -> Result<Value, ExecutionError> {
+    make_object(
+        vm,
+        &[
+            ("rq", Value::Integer(room.q as i64)),
+            ("rr", Value::Integer(room.r as i64)),
+        ],
+    )
+}
+
+/// Create a new Cao-Lang Object holding `values` under the keys `"0"`..`"{length - 1}"`,
+/// and the number of values under `"length"`
+pub fn make_list(
+    vm: &mut Vm<ScriptExecutionData>,
+    values: &[Value],
+) -> Result<Value, ExecutionError> {
+    let keys = (0..values.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+    let mut fields = Vec::with_capacity(values.len() + 1);
+    fields.push(("length", Value::Integer(values.len() as i64)));
+    fields.extend(
+        keys.iter()
+            .map(|key| key.as_str())
+            .zip(values.iter().copied()),
+    );
+    make_object(vm, &fields)
+}
+
+/// Create a new Cao-Lang Object with the given fields
+pub fn make_object(
+    vm: &mut Vm<ScriptExecutionData>,
+    fields: &[(&str, Value)],
+) -> Result<Value, ExecutionError> {
+    let mut table = vm.init_table()?;
+    for (key, value) in fields {
+        let handle = Value::String(init_string(vm, key)?);
+        unsafe { table.as_mut().insert(handle, *value) }.map_err(|err| {
+            error!("Failed to set field {} {:?}", key, err);
+            ExecutionError::TaskFailure("Internal Error".to_string())
+        })?;
+    }
+    Ok(Value::Object(table.as_ptr()))
+}
+
+/// Longest string [init_string](init_string) can allocate, in bytes
+const MAX_STRING_LEN: usize = 255;
+
+/// Layout of Cao-Lang strings: the length followed by the utf8 payload
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ScriptString<const N: usize> {
+    len: u32,
+    payload: [u8; N],
+}
+
+fn write_string<const N: usize>(
+    vm: &mut Vm<ScriptExecutionData>,
+    value: &str,
+) -> Result<StrPointer, ExecutionError> {
+    let mut string = ScriptString {
+        len: value.len() as u32,
+        payload: [0; N],
+    };
+    string.payload[..value.len()].copy_from_slice(value.as_bytes());
+    let ptr = vm.runtime_data.write_to_memory(string)?;
+    Ok(StrPointer(ptr as *mut u8))
+}
+
+/// Allocate a new Cao-Lang string in the Vm's memory
+pub fn init_string(
+    vm: &mut Vm<ScriptExecutionData>,
+    value: &str,
+) -> Result<StrPointer, ExecutionError> {
+    // the Vm only exposes allocation of sized types, so round up to a few size classes
+    match value.len() {
+        0..=12 => write_string::<12>(vm, value),
+        13..=60 => write_string::<60>(vm, value),
+        61..=MAX_STRING_LEN => write_string::<MAX_STRING_LEN>(vm, value),
+        len => {
+            error!("String of length {} is too long: {}", len, value);
+            Err(ExecutionError::TaskFailure("Internal Error".to_string()))
+        }
+    }
+}
+
 fn _get_parse_coordinate(point: &FieldTable, key: &str) -> Result<i32, ExecutionError> {
     let rq = point
         .get_value(Handle::from_str(key).unwrap())
@@ -252,109 +497,324 @@ fn _get_parse_coordinate(point: &FieldTable, key: &str) -> Result<i32, Execution
     })
 }
 
+/// Declares a row of the import table.
+///
+/// Actions output an `ActionResult`, queries a `QueryResult`, see [ImportKind](ImportKind).
+/// Other functions declare their outputs.
+macro_rules! import_row {
+    (action $name:tt, $description:tt, [$($input:tt),*], $fo:expr $(,)?) => {
+        import_row!(@row Action, $name, $description, [$($input),*], ["ActionResult"], $fo)
+    };
+    (query $name:tt, $description:tt, [$($input:tt),*], $fo:expr $(,)?) => {
+        import_row!(@row Query, $name, $description, [$($input),*], ["QueryResult"], $fo)
+    };
+    (function $name:tt, $description:tt, [$($input:tt),*], [$($output:tt),*], $fo:expr $(,)?) => {
+        import_row!(@row Function, $name, $description, [$($input),*], [$($output),*], $fo)
+    };
+    (@row $kind:ident, $name:tt, $description:tt, [$($input:tt),*], [$($output:tt),*], $fo:expr) => {
+        FunctionRow {
+            desc: subprogram_description!(
+                $name,
+                $description,
+                SubProgramType::Function,
+                [$($input),*],
+                [$($output),*],
+                []
+            ),
+            kind: ImportKind::$kind,
+            fo: ImportKind::$kind.wrap($fo),
+        }
+    };
+}
+
 /// Bootstrap the game API in the Vm
+///
+/// This table is the single source of the imports: the Vm, the schema served to clients and
+/// the [docs](Schema::docs) are generated from it.
 pub fn make_import() -> Schema {
     Schema {
         imports: vec![
-            FunctionRow {
-                desc: subprogram_description!(
-                    "console_log",
-                    "Log a string",
-                    SubProgramType::Function,
-                    ["Text"],
-                    [],
-                    []
-                ),
-                fo: Box::new(into_f1(console_log)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "mine",
-                    "Mine the target resource",
-                    SubProgramType::Function,
-                    ["EntityId"],
-                    ["OperationResult"],
-                    []
-                ),
-                fo: Box::new(into_f1(bots::mine_resource)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "approach_entity",
-                    "Move the bot to the given Entity",
-                    SubProgramType::Function,
-                    ["EntityId"],
-                    ["OperationResult"],
-                    []
-                ),
-                fo: Box::new(into_f1(bots::approach_entity)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "move_to_position",
-                    "Move the bot to the given Axial",
-                    SubProgramType::Function,
-                    ["Axial coordinate"],
-                    ["OperationResult"],
-                    []
-                ),
-                fo: Box::new(into_f1(bots::move_bot_to_position)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "find_closest",
-                    "Find an object of type `FindConstant`, closest to the current entity. Returns `Nil` if no such entity is found",
-                    SubProgramType::Function,
-                    ["FindConstant"],
-                    ["EntityId"],
-                    []
-                ),
-                fo: Box::new(into_f1(find_api::find_closest_by_range)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "unload",
-                    "Unload resources",
-                    SubProgramType::Function,
-                    ["Integer", "Resource", "EntityId"],
-                    ["OperationResult"],
-                    []
-                ),
-                fo: Box::new(into_f3(bots::unload)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "parse_find_constant",
-                    "Converts string literal to a find constant",
-                    SubProgramType::Function,
-                    ["Text"],
-                    ["FindConstant"],
-                    []
-                ),
-                fo: Box::new(into_f1(find_api::parse_find_constant)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "melee_attack",
-                    "Attempts to strike the target entity",
-                    SubProgramType::Function,
-                    ["EntityId"],
-                    ["OperationResult"],
-                    []
-                ),
-                fo: Box::new(into_f1(bots::melee_attack)),
-            },
-            FunctionRow {
-                desc: subprogram_description!(
-                    "say",
-                    "Says a given short message",
-                    SubProgramType::Function,
-                    ["Text"],
-                    [],
-                    []
-                ),
-                fo: Box::new(into_f1(say)),
-            },
+            import_row!(
+                function "console_log",
+                "Log a string",
+                ["Text"],
+                [],
+                into_f1(console_log)
+            ),
+            import_row!(
+                action "mine",
+                "Mine the target resource",
+                ["EntityId"],
+                into_f1(bots::mine_resource)
+            ),
+            import_row!(
+                action "approach_entity",
+                "Move the bot to the given Entity",
+                ["EntityId"],
+                into_f1(bots::approach_entity)
+            ),
+            import_row!(
+                action "move_to_position",
+                "Move the bot to the given Axial",
+                ["Axial coordinate"],
+                into_f1(bots::move_bot_to_position)
+            ),
+            import_row!(
+                query "find_closest",
+                "Find an object of type `FindConstant`, closest to the current entity. Not found if there is no such entity",
+                ["FindConstant"],
+                into_f1(find_api::find_closest_by_range)
+            ),
+            import_row!(
+                query "find_closest_where",
+                "Find an object of type `FindConstant`, closest to the current entity, that passes the filter Object. The filter may set the inclusive bounds `minEnergy`, `maxEnergy`, `minHp`, `maxHp`, `minCarry` and `maxCarry`. Not found if there is no such entity",
+                ["FindConstant", "Object"],
+                into_f2(find_api::find_closest_with_filter)
+            ),
+            import_row!(
+                function "find_all_in_range",
+                "Find all objects of type `FindConstant` within `radius` of the current entity, closest first. Returns an Object with the number of entities in `length`, and the EntityIds in `0`..`length - 1`",
+                ["FindConstant", "Integer"],
+                ["Object"],
+                into_f2(find_api::find_all_in_range)
+            ),
+            import_row!(
+                function "count_in_range",
+                "Count the objects of type `FindConstant` within `radius` of the current entity",
+                ["FindConstant", "Integer"],
+                ["Integer"],
+                into_f2(find_api::count_in_range)
+            ),
+            import_row!(
+                action "unload",
+                "Unload the given amount of a carried resource into the target. Fails with `InvalidTarget` if the target can not store the resource",
+                ["Integer", "Resource", "EntityId"],
+                into_f3(bots::unload)
+            ),
+            import_row!(
+                action "withdraw",
+                "Withdraw the given amount of a resource from the inventory of the target into the carry of the current entity. Fails with `NotOwner` if the target is not owned by the user",
+                ["EntityId", "Resource", "Integer"],
+                into_f3(bots::withdraw)
+            ),
+            import_row!(
+                function "parse_resource",
+                "Converts string literal to a resource. One of: `Energy`, `Mineral`, `Crystal`",
+                ["Text"],
+                ["Resource"],
+                into_f1(bots::parse_resource)
+            ),
+            import_row!(
+                function "parse_find_constant",
+                "Converts string literal to a find constant. One of: `Resource`, `Spawn`, `EnemyBot`, `OwnBot`, `EnemyStructure`, `OwnStructure`, `AnyStructure`, `DamagedOwnBot`, `EmptyResource`, `ConstructionSite`",
+                ["Text"],
+                ["FindConstant"],
+                into_f1(find_api::parse_find_constant)
+            ),
+            import_row!(
+                action "melee_attack",
+                "Attempts to strike the target entity",
+                ["EntityId"],
+                into_f1(bots::melee_attack)
+            ),
+            import_row!(
+                action "ranged_attack",
+                "Attempts to shoot the target entity. The damage falls off with the distance to the target. Fails with `NotInRange` if the target is out of the range of the bot",
+                ["EntityId"],
+                into_f1(bots::ranged_attack)
+            ),
+            import_row!(
+                action "heal",
+                "Restores the hp of the target bot. Fails with `Full` if the target is at full hp",
+                ["EntityId"],
+                into_f1(bots::heal)
+            ),
+            import_row!(
+                action "repair",
+                "Restores the hp of the target structure, spending 1 carried energy per hp. Fails with `Empty` if the bot carries no energy",
+                ["EntityId"],
+                into_f1(bots::repair)
+            ),
+            import_row!(
+                action "build",
+                "Places a construction site of the structure at the position, in the room of the current entity. Bots build the structure by unloading energy into the site. Fails with `InvalidTarget` if the position is not walkable or taken",
+                ["WorldPosition", "StructureType"],
+                into_f2(construction_api::build)
+            ),
+            import_row!(
+                function "parse_structure_type",
+                "Converts string literal to a structure type. One of: `Container`, `Tower`",
+                ["Text"],
+                ["StructureType"],
+                into_f1(construction_api::parse_structure_type)
+            ),
+            import_row!(
+                function "say",
+                "Says a given short message",
+                ["Text"],
+                [],
+                into_f1(say)
+            ),
+            import_row!(
+                query "cpu_remaining",
+                "Returns the number of instructions your scripts may still execute in this tick. Not found if the entity has no owner",
+                [],
+                cpu_remaining
+            ),
+            import_row!(
+                function "whoami",
+                "Returns the EntityId of the entity executing the script",
+                [],
+                ["EntityId"],
+                entity_api::whoami
+            ),
+            import_row!(
+                query "get_hp",
+                "Returns the `hp` and `hpMax` of the entity. Not found if the entity has no hitpoints",
+                ["EntityId"],
+                into_f1(entity_api::get_hp)
+            ),
+            import_row!(
+                query "get_carry",
+                "Returns the `carry` and `carryMax` of the entity. Not found if the entity can not carry resources",
+                ["EntityId"],
+                into_f1(entity_api::get_carry)
+            ),
+            import_row!(
+                query "get_inventory",
+                "Returns the resources stored by the entity: the total in `stored`, the `capacity` and the amount of each resource in `energy`, `mineral` and `crystal`. Not found if the entity has no inventory. Bots carry their resources, see `get_carry`",
+                ["EntityId"],
+                into_f1(entity_api::get_inventory)
+            ),
+            import_row!(
+                query "get_energy",
+                "Returns the `energy` and `energyMax` of the entity. Not found if the entity has no energy. Containers store their energy in their inventory, see `get_inventory`",
+                ["EntityId"],
+                into_f1(entity_api::get_energy)
+            ),
+            import_row!(
+                query "get_position",
+                "Returns the WorldPosition of the entity. Not found if the entity has no position",
+                ["EntityId"],
+                into_f1(entity_api::get_position)
+            ),
+            import_row!(
+                query "get_owner",
+                "Returns the id of the user owning the entity. Not found if the entity has no owner",
+                ["EntityId"],
+                into_f1(entity_api::get_owner)
+            ),
+            import_row!(
+                query "get_terrain",
+                "Returns the terrain at the given position as an Integer: one of 0 (`empty`), 1 (`plain`), 2 (`bridge`) or 3 (`wall`). `plain` and `bridge` tiles are walkable. Not found if the position is not on the map",
+                ["WorldPosition"],
+                into_f1(map_api::get_terrain)
+            ),
+            import_row!(
+                query "get_room_exits",
+                "Returns the exits of the room, as an Object with the number of exits in `length`, and the exits in `0`..`length - 1`. Each exit holds the neighbouring room in `rq`, `rr` and the bridged section of the room's edge in `offsetStart`, `offsetEnd`. Not found if the room does not exist",
+                ["Room"],
+                into_f1(map_api::get_room_exits)
+            ),
+            import_row!(
+                function "list_neighbour_rooms",
+                "Returns the existing rooms next to the room, as an Object with the number of rooms in `length`, and the rooms in `0`..`length - 1`",
+                ["Room"],
+                ["Object"],
+                into_f1(map_api::list_neighbour_rooms)
+            ),
+            import_row!(
+                query "room_of",
+                "Returns the room the entity is in, as an Object with the fields `rq` and `rr`. Not found if the entity has no position",
+                ["EntityId"],
+                into_f1(map_api::room_of)
+            ),
+            import_row!(
+                query "find_path",
+                "Finds a path from the first position to the next to the second one. Returns an Object with the number of steps in `length`, and the positions in walking order in `0`..`length - 1`. Not found if there is no path. If the target is in another room, the path leads to the exit towards it. Path finding consumes instructions of the script, proportional to the steps taken",
+                ["WorldPosition", "WorldPosition"],
+                into_f2(map_api::find_path)
+            ),
+            import_row!(
+                query "path_distance",
+                "Returns the number of steps it takes to walk from the first position to the next to the second one. Not found if there is no path. If the target is in another room, the distance to the exit towards it is returned. Path finding consumes instructions of the script, proportional to the steps taken",
+                ["WorldPosition", "WorldPosition"],
+                into_f2(map_api::path_distance)
+            ),
+            import_row!(
+                query "get_memory",
+                "Returns the value stored under the key in the memory of the entity. Not found if the key is not set. The memory is kept between ticks",
+                ["Text"],
+                into_f1(memory_api::get_memory)
+            ),
+            import_row!(
+                action "set_memory",
+                "Stores a value under the key in the memory of the entity. Only integers, floats and texts can be stored, Objects fail with `InvalidInput`. `Nil` removes the key. Fails with `Full` if the memory would exceed its size limit",
+                ["Text", "Value"],
+                into_f2(memory_api::set_memory)
+            ),
+            import_row!(
+                query "get_user_memory",
+                "Returns the value stored under the key in the memory shared by the user's entities. Not found if the key is not set",
+                ["Text"],
+                into_f1(memory_api::get_user_memory)
+            ),
+            import_row!(
+                action "set_user_memory",
+                "Stores a value under the key in the memory shared by the user's entities. Only integers, floats and texts can be stored, like in `set_memory`. Other entities see the change in the next tick. Fails with `Full` if the memory would exceed its size limit",
+                ["Text", "Value"],
+                into_f2(memory_api::set_user_memory)
+            ),
+            import_row!(
+                action "send_message",
+                "Posts a message to the named channel of the user. Messages can be read by the user's entities in the next tick. The payload may be an integer, float or text. Fails with `Full` if the message is too large",
+                ["Text", "Value"],
+                into_f2(message_api::send_message)
+            ),
+            import_row!(
+                action "send_message_to",
+                "Sends a message to an entity of the user, readable by it in the next tick. The payload may be an integer, float or text. Fails with `Full` if the message is too large",
+                ["EntityId", "Value"],
+                into_f2(message_api::send_message_to)
+            ),
+            import_row!(
+                function "read_channel",
+                "Returns the messages posted to the named channel of the user in the last tick, as an Object with the number of messages in `length`, and the messages in `0`..`length - 1`. Each message holds the `sender` and the `payload`",
+                ["Text"],
+                ["Object"],
+                into_f1(message_api::read_channel)
+            ),
+            import_row!(
+                function "read_inbox",
+                "Returns the messages sent to the current entity in the last tick, as an Object with the number of messages in `length`, and the messages in `0`..`length - 1`. Each message holds the `sender` and the `payload`",
+                [],
+                ["Object"],
+                message_api::read_inbox
+            ),
+            import_row!(
+                action "spawn_bot",
+                "Queues a bot in the spawn. The body Object may set `hp`, `carry`, `melee`, `ranged`, `armor`, `heal` and `decayInterval`, missing fields take the value of the default body. The body is paid for from the energy of the spawn when the bot starts spawning. Fails with `InvalidInput` if the spawn can not store enough energy to pay for the body",
+                ["EntityId", "Object"],
+                into_f2(spawn_api::spawn_bot)
+            ),
+            import_row!(
+                action "set_continuous_spawning",
+                "Turns continuous spawning of the spawn on (non-zero) or off (zero). Continuous spawns queue a bot with the default body whenever their queue is empty",
+                ["EntityId", "Integer"],
+                into_f2(spawn_api::set_continuous_spawning)
+            ),
+            import_row!(
+                action "set_spawn_script",
+                "Sets the script the bots spawned by the current spawn run, given the id of the script. `Nil` restores the default script of the user",
+                ["Text"],
+                into_f1(spawn_api::set_spawn_script)
+            ),
+            import_row!(
+                action "transfer_energy",
+                "Transfers as much energy of the current structure to the target as it can hold. The target must be next to the structure",
+                ["EntityId"],
+                into_f1(structure_api::transfer_energy)
+            ),
         ],
     }
 }
@@ -3,14 +3,16 @@ use crate::{
     components::{self, Resource},
     indices::{EntityId, UserId, WorldPosition},
     intents::{
-        check_dropoff_intent, check_melee_intent, check_mine_intent, check_move_intent,
-        CachePathIntent, DropoffIntent, MeleeIntent, MineIntent, MoveIntent, MutPathCacheIntent,
-        PathCacheIntentAction,
+        check_dropoff_intent, check_heal_intent, check_melee_intent, check_mine_intent,
+        check_move_intent, check_ranged_intent, check_repair_intent, check_withdraw_intent,
+        CachePathIntent, DropoffIntent, HealIntent, MeleeIntent, MineIntent, MoveIntent,
+        MutPathCacheIntent, PathCacheIntentAction, RangedIntent, RepairIntent, WithdrawIntent,
     },
     pathfinding, profile,
     storage::views::FromWorld,
 };
 use crate::{prelude::World, terrain::TileTerrainType};
+use cao_lang::StrPointer;
 use std::convert::{TryFrom, TryInto};
 use tracing::{debug, error, trace, warn};
 
@@ -44,6 +46,84 @@ pub fn melee_attack(vm: &mut Vm<ScriptExecutionData>, target: i64) -> Result<(),
     Ok(())
 }
 
+pub fn ranged_attack(
+    vm: &mut Vm<ScriptExecutionData>,
+    target: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("ranged-attack");
+    trace!("ranged_attack {:?}", target);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = RangedIntent {
+        attacker: aux.entity_id,
+        defender: target,
+    };
+
+    let res = check_ranged_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.ranged_attack_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+pub fn heal(vm: &mut Vm<ScriptExecutionData>, target: EntityId) -> Result<(), ExecutionError> {
+    profile!("heal");
+    trace!("heal {:?}", target);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = HealIntent {
+        healer: aux.entity_id,
+        target,
+    };
+
+    let res = check_heal_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.heal_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+pub fn repair(vm: &mut Vm<ScriptExecutionData>, structure: EntityId) -> Result<(), ExecutionError> {
+    profile!("repair");
+    trace!("repair {:?}", structure);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = RepairIntent {
+        bot: aux.entity_id,
+        structure,
+    };
+
+    let res = check_repair_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.repair_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
 pub fn unload(
     vm: &mut Vm<ScriptExecutionData>,
     amount: i64,
@@ -92,6 +172,88 @@ pub fn unload(
     Ok(())
 }
 
+pub fn withdraw(
+    vm: &mut Vm<ScriptExecutionData>,
+    target: i64,
+    ty: Resource,
+    amount: i64,
+) -> Result<(), ExecutionError> {
+    profile!("withdraw");
+    let aux = vm.get_aux();
+
+    let amount = TryFrom::try_from(amount).map_err(|e| {
+        ExecutionError::invalid_argument(format!("withdraw called with invalid amount: {}", e))
+    })?;
+    let target: u64 = target.try_into().map_err(|_| {
+        warn!("withdraw called without a valid target");
+        ExecutionError::invalid_argument("withdraw called without a valid target".to_owned())
+    })?;
+    let target: EntityId = EntityId::from(target);
+
+    trace!(
+        "withdraw: amount: {} type: {:?} target: {:?}, {}",
+        amount,
+        ty,
+        target,
+        aux
+    );
+
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+
+    let withdraw_intent = WithdrawIntent {
+        bot: aux.entity_id,
+        structure: target,
+        amount,
+        ty,
+    };
+
+    let checkresult = check_withdraw_intent(
+        &withdraw_intent,
+        user_id,
+        FromWorld::from_world(aux.storage()),
+    );
+    if let OperationResult::Ok = checkresult {
+        vm.get_aux_mut().intents.withdraw_intent = Some(withdraw_intent);
+    }
+    vm.stack_push(checkresult)?;
+    Ok(())
+}
+
+/// Converts a resource name to the Resource constant taken by `unload`
+pub fn parse_resource(
+    vm: &mut Vm<ScriptExecutionData>,
+    param: StrPointer,
+) -> Result<(), ExecutionError> {
+    profile!("parse_resource");
+    let param = unsafe {
+        param.get_str().ok_or_else(|| {
+            ExecutionError::invalid_argument(
+                "parse_resource called with non-string param".to_owned(),
+            )
+        })?
+    };
+    let resource = match param {
+        "energy" | "ENERGY" | "Energy" => Resource::Energy,
+        "mineral" | "MINERAL" | "Mineral" => Resource::Mineral,
+        "crystal" | "CRYSTAL" | "Crystal" => Resource::Crystal,
+        _ => {
+            trace!("parse_resource got an invalid resource {}", param);
+            return Err(ExecutionError::invalid_argument(format!(
+                "parse_resource got an invalid resource {}",
+                param
+            )));
+        }
+    };
+    vm.stack_push(resource as i64)?;
+    Ok(())
+}
+
 pub fn mine_resource(vm: &mut Vm<ScriptExecutionData>, target: i64) -> Result<(), ExecutionError> {
     profile!("mine_resource");
 
@@ -507,4 +669,60 @@ mod tests {
         assert_eq!(bot, bot_id);
         assert_eq!(position.room, next_room);
     }
+
+    #[test]
+    fn withdraw_checks_the_owner_of_the_target() {
+        let mut storage = World::new();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let bot_id = storage.insert_entity();
+        let mine = storage.insert_entity();
+        let theirs = storage.insert_entity();
+        let at = |q| {
+            PositionComponent(WorldPosition {
+                room: Axial::new(0, 0),
+                pos: Axial::new(q, 0),
+            })
+        };
+        let mut inventory = InventoryComponent::new(100);
+        inventory.add(Resource::Crystal, 10);
+        query!(
+            mutate
+            storage
+            {
+                EntityId, Bot, .insert(bot_id);
+                EntityId, CarryComponent, .insert(bot_id, CarryComponent::new(50));
+                EntityId, PositionComponent, .insert(bot_id, at(1));
+                EntityId, PositionComponent, .insert(mine, at(0));
+                EntityId, PositionComponent, .insert(theirs, at(2));
+                EntityId, InventoryComponent, .insert(mine, inventory);
+                EntityId, InventoryComponent, .insert(theirs, inventory);
+                EntityId, OwnedEntity, .insert(bot_id, OwnedEntity { owner_id: user_id });
+                EntityId, OwnedEntity, .insert(mine, OwnedEntity { owner_id: user_id });
+                EntityId, OwnedEntity, .insert(theirs, OwnedEntity { owner_id: UserId::default() });
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            bot_id,
+            Some(user_id),
+            crate::systems::script_execution::get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+
+        withdraw(&mut vm, u64::from(theirs) as i64, Resource::Crystal, 10).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::NotOwner);
+        assert!(vm.get_aux().intents.withdraw_intent.is_none());
+
+        withdraw(&mut vm, u64::from(mine) as i64, Resource::Mineral, 10).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Empty);
+
+        withdraw(&mut vm, u64::from(mine) as i64, Resource::Crystal, 10).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Ok);
+        assert!(vm.get_aux().intents.withdraw_intent.is_some());
+    }
 }
@@ -0,0 +1,162 @@
+//! Construction of structures
+//!
+//! Any entity of a user may place construction sites in its room. Bots build the structure by
+//! unloading energy into the site.
+use super::*;
+use crate::components::StructureType;
+use crate::intents::{check_build_intent, BuildIntent};
+use crate::storage::views::FromWorld;
+use cao_lang::StrPointer;
+use tracing::trace;
+
+/// Place a construction site of the structure at the position
+pub fn build(
+    vm: &mut Vm<ScriptExecutionData>,
+    point: &FieldTable,
+    ty: StructureType,
+) -> Result<(), ExecutionError> {
+    profile!("build");
+    let pos = parse_world_pos(point)?;
+    trace!("build {:?} {:?}", pos, ty);
+
+    let aux = vm.get_aux();
+    let owner_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = BuildIntent {
+        builder: aux.entity_id,
+        owner_id,
+        pos,
+        ty,
+    };
+    let res = check_build_intent(&intent, owner_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.build_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+/// Converts a structure name to the StructureType constant taken by `build`
+pub fn parse_structure_type(
+    vm: &mut Vm<ScriptExecutionData>,
+    param: StrPointer,
+) -> Result<(), ExecutionError> {
+    profile!("parse_structure_type");
+    let param = unsafe {
+        param.get_str().ok_or_else(|| {
+            ExecutionError::invalid_argument(
+                "parse_structure_type called with non-string param".to_owned(),
+            )
+        })?
+    };
+    let ty = match param {
+        "container" | "CONTAINER" | "Container" => StructureType::Container,
+        "tower" | "TOWER" | "Tower" => StructureType::Tower,
+        _ => {
+            trace!(
+                "parse_structure_type got an invalid structure type {}",
+                param
+            );
+            return Err(ExecutionError::invalid_argument(format!(
+                "parse_structure_type got an invalid structure type {}",
+                param
+            )));
+        }
+    };
+    vm.stack_push(ty as i64)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{EntityComponent, OwnedEntity, PositionComponent, TerrainComponent};
+    use crate::indices::{EntityId, Room, UserId};
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+    use crate::terrain::TileTerrainType;
+
+    #[test]
+    fn test_build_checks_the_position() {
+        let mut storage = World::new();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let room = Axial::new(0, 0);
+        let at = |q| WorldPosition {
+            room,
+            pos: Axial::new(q, 3),
+        };
+        let bot_id = storage.insert_entity();
+        query!(
+            mutate
+            storage
+            {
+                EntityId, OwnedEntity, .insert(bot_id, OwnedEntity { owner_id: user_id });
+                EntityId, PositionComponent, .insert(bot_id, PositionComponent(at(1)));
+                WorldPosition, EntityComponent,
+                    .extend_rooms([Room(room)].iter().cloned())
+                    .unwrap();
+                WorldPosition, TerrainComponent,
+                    .extend_rooms([Room(room)].iter().cloned())
+                    .unwrap();
+                WorldPosition, TerrainComponent,
+                    .iter_rooms_mut().for_each(|(_, room)| room.resize(3));
+                WorldPosition, TerrainComponent,
+                    .extend_from_slice(&mut [(at(2), TerrainComponent(TileTerrainType::Plain))])
+                    .unwrap();
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            bot_id,
+            Some(user_id),
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+        fn build_at(
+            vm: &mut Vm<ScriptExecutionData>,
+            q: i64,
+            ty: StructureType,
+        ) -> OperationResult {
+            let point = make_object(
+                vm,
+                &[
+                    ("rq", Value::Integer(0)),
+                    ("rr", Value::Integer(0)),
+                    ("q", Value::Integer(q)),
+                    ("r", Value::Integer(3)),
+                ],
+            )
+            .unwrap();
+            let point = match point {
+                Value::Object(t) => unsafe { &*t },
+                _ => unreachable!(),
+            };
+            build(vm, point, ty).unwrap();
+            vm.stack_pop().try_into().unwrap()
+        }
+
+        // not walkable
+        assert_eq!(
+            build_at(&mut vm, 3, StructureType::Tower),
+            OperationResult::InvalidTarget
+        );
+        assert_eq!(
+            build_at(&mut vm, 2, StructureType::Spawn),
+            OperationResult::InvalidInput
+        );
+        assert!(vm.get_aux().intents.build_intent.is_none());
+        assert_eq!(
+            build_at(&mut vm, 2, StructureType::Tower),
+            OperationResult::Ok
+        );
+        assert!(vm.get_aux().intents.build_intent.is_some());
+    }
+}
@@ -0,0 +1,301 @@
+//! Read-only queries of entity properties
+//!
+//! Queries of components the entity does not have return `Nil`.
+use super::*;
+use crate::components::{
+    CarryComponent, EnergyComponent, HpComponent, InventoryComponent, OwnedEntity,
+    PositionComponent, Resource,
+};
+use crate::indices::EntityId;
+use crate::prelude::World;
+use crate::profile;
+use crate::storage::views::View;
+use crate::tables::{Component, Table};
+use tracing::trace;
+
+/// Push the EntityId of the entity running the script
+pub fn whoami(vm: &mut Vm<ScriptExecutionData>) -> Result<(), ExecutionError> {
+    profile!("whoami");
+    let id: u64 = vm.get_aux().entity_id.into();
+    vm.stack_push(id as i64)?;
+    Ok(())
+}
+
+/// Read the component `C` of `entity_id` and push it converted to a value by `to_value`
+fn push_component<C, F>(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+    to_value: F,
+) -> Result<(), ExecutionError>
+where
+    C: Component<EntityId> + Clone,
+    C::Table: Table<Id = EntityId>,
+    World: crate::storage::HasTable<EntityId, C>,
+    F: FnOnce(&mut Vm<ScriptExecutionData>, C) -> Result<Value, ExecutionError>,
+{
+    let component = {
+        let table: View<EntityId, C> = vm.get_aux().storage().view();
+        table.get(entity_id).cloned()
+    };
+    let value = match component {
+        Some(component) => to_value(vm, component)?,
+        None => {
+            trace!("{:?} has no {}", entity_id, std::any::type_name::<C>());
+            Value::Nil
+        }
+    };
+    vm.stack_push(value)?;
+    Ok(())
+}
+
+pub fn get_hp(vm: &mut Vm<ScriptExecutionData>, entity_id: EntityId) -> Result<(), ExecutionError> {
+    profile!("get_hp");
+    push_component(vm, entity_id, |vm, hp: HpComponent| {
+        make_object(
+            vm,
+            &[
+                ("hp", Value::Integer(hp.hp as i64)),
+                ("hpMax", Value::Integer(hp.hp_max as i64)),
+            ],
+        )
+    })
+}
+
+pub fn get_carry(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("get_carry");
+    push_component(vm, entity_id, |vm, carry: CarryComponent| {
+        make_object(
+            vm,
+            &[
+                ("carry", Value::Integer(carry.carry() as i64)),
+                ("carryMax", Value::Integer(carry.carry_max as i64)),
+                (
+                    "energy",
+                    Value::Integer(carry.resources.get(Resource::Energy) as i64),
+                ),
+                (
+                    "mineral",
+                    Value::Integer(carry.resources.get(Resource::Mineral) as i64),
+                ),
+                (
+                    "crystal",
+                    Value::Integer(carry.resources.get(Resource::Crystal) as i64),
+                ),
+            ],
+        )
+    })
+}
+
+pub fn get_inventory(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("get_inventory");
+    push_component(vm, entity_id, |vm, inventory: InventoryComponent| {
+        make_object(
+            vm,
+            &[
+                ("stored", Value::Integer(inventory.stored() as i64)),
+                ("capacity", Value::Integer(inventory.capacity as i64)),
+                (
+                    "energy",
+                    Value::Integer(inventory.resources.get(Resource::Energy) as i64),
+                ),
+                (
+                    "mineral",
+                    Value::Integer(inventory.resources.get(Resource::Mineral) as i64),
+                ),
+                (
+                    "crystal",
+                    Value::Integer(inventory.resources.get(Resource::Crystal) as i64),
+                ),
+            ],
+        )
+    })
+}
+
+pub fn get_energy(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("get_energy");
+    push_component(vm, entity_id, |vm, energy: EnergyComponent| {
+        make_object(
+            vm,
+            &[
+                ("energy", Value::Integer(energy.energy as i64)),
+                ("energyMax", Value::Integer(energy.energy_max as i64)),
+            ],
+        )
+    })
+}
+
+/// Push the position of the entity in the same format [parse_world_pos](parse_world_pos)
+/// expects
+pub fn get_position(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("get_position");
+    push_component(vm, entity_id, |vm, pos: PositionComponent| {
+        world_pos_to_object(vm, pos.0)
+    })
+}
+
+/// Push the id of the user owning the entity, as a string
+pub fn get_owner(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("get_owner");
+    push_component(vm, entity_id, |vm, owner: OwnedEntity| {
+        let owner_id = owner.owner_id.0.to_string();
+        let ptr = init_string(vm, owner_id.as_str())?;
+        Ok(Value::String(ptr))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indices::UserId;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+
+    fn read_field(value: Value, key: &str) -> Value {
+        match value {
+            Value::Object(table) => unsafe {
+                (*table)
+                    .get_value(Handle::from_str(key).unwrap())
+                    .unwrap_or_default()
+            },
+            _ => panic!("Expected an object, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_get_hp() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let other_id = storage.insert_entity();
+        query!(
+            mutate
+            storage
+            {
+                EntityId, HpComponent, .insert(entity_id, HpComponent { hp: 42, hp_max: 100 });
+            }
+        );
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), other_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        get_hp(&mut vm, entity_id).unwrap();
+        let hp = vm.stack_pop();
+        assert!(matches!(read_field(hp, "hp"), Value::Integer(42)));
+        assert!(matches!(read_field(hp, "hpMax"), Value::Integer(100)));
+
+        get_hp(&mut vm, other_id).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Nil));
+    }
+
+    #[test]
+    fn test_get_inventory() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let other_id = storage.insert_entity();
+        let mut inventory = InventoryComponent::new(100);
+        inventory.resources.add(Resource::Energy, 30);
+        inventory.resources.add(Resource::Crystal, 12);
+        query!(
+            mutate
+            storage
+            {
+                EntityId, InventoryComponent, .insert(entity_id, inventory);
+            }
+        );
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), other_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        get_inventory(&mut vm, entity_id).unwrap();
+        let inventory = vm.stack_pop();
+        let field = |name| read_field(inventory, name);
+        assert!(matches!(field("stored"), Value::Integer(42)));
+        assert!(matches!(field("capacity"), Value::Integer(100)));
+        assert!(matches!(field("energy"), Value::Integer(30)));
+        assert!(matches!(field("mineral"), Value::Integer(0)));
+        assert!(matches!(field("crystal"), Value::Integer(12)));
+
+        get_inventory(&mut vm, other_id).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Nil));
+    }
+
+    #[test]
+    fn test_get_position_roundtrips() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let pos = WorldPosition {
+            room: Axial::new(1, 2),
+            pos: Axial::new(3, 4),
+        };
+        query!(
+            mutate
+            storage
+            {
+                EntityId, PositionComponent, .insert(entity_id, PositionComponent(pos));
+            }
+        );
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        get_position(&mut vm, entity_id).unwrap();
+        match vm.stack_pop() {
+            Value::Object(table) => {
+                assert_eq!(unsafe { parse_world_pos(&*table) }.unwrap(), pos);
+            }
+            value => panic!("Expected an object, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_get_owner() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let owner_id = UserId(uuid::Uuid::new_v4());
+        query!(
+            mutate
+            storage
+            {
+                EntityId, OwnedEntity, .insert(entity_id, OwnedEntity { owner_id });
+            }
+        );
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        get_owner(&mut vm, entity_id).unwrap();
+        let owner = unsafe { vm.stack_pop().as_str() };
+        assert_eq!(owner, Some(owner_id.0.to_string().as_str()));
+    }
+
+    #[test]
+    fn test_whoami() {
+        let storage = World::new();
+        let entity_id = EntityId::new(12, 3);
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        whoami(&mut vm).unwrap();
+        let id: EntityId = vm.stack_pop().try_into().unwrap();
+        assert_eq!(id, entity_id);
+    }
+}
@@ -1,18 +1,33 @@
 use super::*;
 use crate::components::{EntityComponent, PositionComponent};
-use crate::indices::WorldPosition;
+use crate::indices::{UserId, WorldPosition};
 use crate::profile;
+use crate::tables::morton_table::MortonTable;
 use crate::world::World;
 use cao_lang::{prelude::*, StrPointer};
 use std::convert::TryFrom;
 use tracing::{trace, warn};
 
+/// Larger radii are clamped
+pub const MAX_FIND_RADIUS: u32 = u16::MAX as u32;
+
 #[derive(Debug, Clone, Copy)]
 #[repr(i32)]
 pub enum FindConstant {
     Resource = 1,
+    /// Own spawns
     Spawn = 2,
     EnemyBot = 3,
+    OwnBot = 4,
+    EnemyStructure = 5,
+    OwnStructure = 6,
+    AnyStructure = 7,
+    /// Own bots that have less than maximum hp
+    DamagedOwnBot = 8,
+    /// Resources that have been depleted and are waiting to respawn
+    EmptyResource = 9,
+    /// Own construction sites
+    ConstructionSite = 10,
 }
 
 impl TryFrom<Value> for FindConstant {
@@ -22,6 +37,13 @@ impl TryFrom<Value> for FindConstant {
             Value::Integer(1) => FindConstant::Resource,
             Value::Integer(2) => FindConstant::Spawn,
             Value::Integer(3) => FindConstant::EnemyBot,
+            Value::Integer(4) => FindConstant::OwnBot,
+            Value::Integer(5) => FindConstant::EnemyStructure,
+            Value::Integer(6) => FindConstant::OwnStructure,
+            Value::Integer(7) => FindConstant::AnyStructure,
+            Value::Integer(8) => FindConstant::DamagedOwnBot,
+            Value::Integer(9) => FindConstant::EmptyResource,
+            Value::Integer(10) => FindConstant::ConstructionSite,
             _ => return Err(i),
         };
         Ok(op)
@@ -46,6 +68,15 @@ pub fn parse_find_constant(
         "resource" | "RESOURCE" | "Resource" => FindConstant::Resource,
         "spawn" | "SPAWN" | "Spawn" => FindConstant::Spawn,
         "enemy_bot" | "ENEMY_BOT" | "EnemyBot" => FindConstant::EnemyBot,
+        "own_bot" | "OWN_BOT" | "OwnBot" => FindConstant::OwnBot,
+        "enemy_structure" | "ENEMY_STRUCTURE" | "EnemyStructure" => FindConstant::EnemyStructure,
+        "own_structure" | "OWN_STRUCTURE" | "OwnStructure" => FindConstant::OwnStructure,
+        "any_structure" | "ANY_STRUCTURE" | "AnyStructure" => FindConstant::AnyStructure,
+        "damaged_own_bot" | "DAMAGED_OWN_BOT" | "DamagedOwnBot" => FindConstant::DamagedOwnBot,
+        "empty_resource" | "EMPTY_RESOURCE" | "EmptyResource" => FindConstant::EmptyResource,
+        "construction_site" | "CONSTRUCTION_SITE" | "ConstructionSite" => {
+            FindConstant::ConstructionSite
+        }
         _ => {
             trace!(
                 "parse_find_constant got an invalid constant value {}",
@@ -98,24 +129,22 @@ pub fn find_closest_by_range(
 }
 
 impl FindConstant {
-    pub fn execute(
+    /// Returns a predicate that tells if an entity matches this constant, from the point of
+    /// view of `user_id`
+    pub fn predicate<'a>(
         self,
-        vm: &mut Vm<ScriptExecutionData>,
-        position: WorldPosition,
-    ) -> Result<(), ExecutionError> {
-        trace!("Executing find {:?}", self);
-
-        let storage = vm.get_aux().storage();
-        let user_id = vm.get_aux().user_id;
-        let candidate = match self {
+        storage: &'a World,
+        user_id: Option<UserId>,
+    ) -> Box<dyn Fn(EntityId) -> bool + 'a> {
+        match self {
             FindConstant::Resource => {
                 let resources = storage.view::<EntityId, components::ResourceComponent>();
-                find_closest_entity_impl(storage, position, |id| resources.contains(id))
+                Box::new(move |id| resources.contains(id))
             }
             FindConstant::Spawn => {
                 let owner = storage.view::<EntityId, components::OwnedEntity>();
                 let spawns = storage.view::<EntityId, components::SpawnComponent>();
-                find_closest_entity_impl(storage, position, |id| {
+                Box::new(move |id| {
                     spawns.contains(id)
                         && owner.get(id).map(|owner_id| owner_id.owner_id) == user_id
                 })
@@ -123,11 +152,89 @@ impl FindConstant {
             FindConstant::EnemyBot => {
                 let owner = storage.view::<EntityId, components::OwnedEntity>();
                 let bots = storage.view::<EntityId, components::Bot>();
-                find_closest_entity_impl(storage, position, |id| {
+                Box::new(move |id| {
                     bots.contains(&id) && owner.get(id).map(|owner_id| owner_id.owner_id) != user_id
                 })
             }
-        }?;
+            FindConstant::OwnBot => {
+                let owner = storage.view::<EntityId, components::OwnedEntity>();
+                let bots = storage.view::<EntityId, components::Bot>();
+                Box::new(move |id| {
+                    bots.contains(&id) && owner.get(id).map(|owner_id| owner_id.owner_id) == user_id
+                })
+            }
+            FindConstant::EnemyStructure => {
+                let owner = storage.view::<EntityId, components::OwnedEntity>();
+                let structures = storage.view::<EntityId, components::Structure>();
+                Box::new(move |id| {
+                    structures.contains(&id)
+                        && owner.get(id).map(|owner_id| owner_id.owner_id) != user_id
+                })
+            }
+            FindConstant::OwnStructure => {
+                let owner = storage.view::<EntityId, components::OwnedEntity>();
+                let structures = storage.view::<EntityId, components::Structure>();
+                Box::new(move |id| {
+                    structures.contains(&id)
+                        && owner.get(id).map(|owner_id| owner_id.owner_id) == user_id
+                })
+            }
+            FindConstant::AnyStructure => {
+                let structures = storage.view::<EntityId, components::Structure>();
+                Box::new(move |id| structures.contains(&id))
+            }
+            FindConstant::DamagedOwnBot => {
+                let owner = storage.view::<EntityId, components::OwnedEntity>();
+                let bots = storage.view::<EntityId, components::Bot>();
+                let hp = storage.view::<EntityId, components::HpComponent>();
+                Box::new(move |id| {
+                    bots.contains(&id)
+                        && owner.get(id).map(|owner_id| owner_id.owner_id) == user_id
+                        && hp.get(id).map(|hp| hp.hp < hp.hp_max).unwrap_or(false)
+                })
+            }
+            FindConstant::EmptyResource => {
+                let resources = storage.view::<EntityId, components::ResourceComponent>();
+                let energy = storage.view::<EntityId, components::EnergyComponent>();
+                Box::new(move |id| {
+                    resources.contains(id) && energy.get(id).map(|e| e.energy == 0).unwrap_or(true)
+                })
+            }
+            FindConstant::ConstructionSite => {
+                let owner = storage.view::<EntityId, components::OwnedEntity>();
+                let sites = storage.view::<EntityId, components::ConstructionSiteComponent>();
+                Box::new(move |id| {
+                    sites.contains(id) && owner.get(id).map(|owner_id| owner_id.owner_id) == user_id
+                })
+            }
+        }
+    }
+
+    pub fn execute(
+        self,
+        vm: &mut Vm<ScriptExecutionData>,
+        position: WorldPosition,
+    ) -> Result<(), ExecutionError> {
+        trace!("Executing find {:?}", self);
+
+        self.execute_with_filter(vm, position, FindFilter::default())
+    }
+
+    /// Find the closest entity matching both `self` and `filter`
+    pub fn execute_with_filter(
+        self,
+        vm: &mut Vm<ScriptExecutionData>,
+        position: WorldPosition,
+        filter: FindFilter,
+    ) -> Result<(), ExecutionError> {
+        trace!("Executing find {:?} {:?}", self, filter);
+
+        let candidate = {
+            let storage = vm.get_aux().storage();
+            let predicate = self.predicate(storage, vm.get_aux().user_id);
+            let filter = filter.predicate(storage);
+            find_closest_entity_impl(storage, position, |id| predicate(id) && filter(id))?
+        };
         match candidate {
             Some(entity) => {
                 tracing::debug!("Found entity {:?}", entity);
@@ -143,6 +250,199 @@ impl FindConstant {
     }
 }
 
+/// Additional conditions on the entities to find, read from a Cao-Lang Object.
+///
+/// Bounds are inclusive. Entities without the component a bound refers to do not match.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FindFilter {
+    pub min_energy: Option<i64>,
+    pub max_energy: Option<i64>,
+    pub min_hp: Option<i64>,
+    pub max_hp: Option<i64>,
+    pub min_carry: Option<i64>,
+    pub max_carry: Option<i64>,
+}
+
+impl FindFilter {
+    /// Reads the fields `minEnergy`, `maxEnergy`, `minHp`, `maxHp`, `minCarry` and `maxCarry`.
+    /// Missing or `Nil` fields are not checked.
+    pub fn parse(table: &FieldTable) -> Result<Self, ExecutionError> {
+        let get = |key: &str| -> Result<Option<i64>, ExecutionError> {
+            match table.get_value(Handle::from_str(key).unwrap()) {
+                None | Some(Value::Nil) => Ok(None),
+                Some(Value::Integer(i)) => Ok(Some(i)),
+                Some(value) => Err(ExecutionError::invalid_argument(format!(
+                    "filter field {} must be an integer, got {:?}",
+                    key, value
+                ))),
+            }
+        };
+        Ok(Self {
+            min_energy: get("minEnergy")?,
+            max_energy: get("maxEnergy")?,
+            min_hp: get("minHp")?,
+            max_hp: get("maxHp")?,
+            min_carry: get("minCarry")?,
+            max_carry: get("maxCarry")?,
+        })
+    }
+
+    pub fn predicate<'a>(self, storage: &'a World) -> impl Fn(EntityId) -> bool + 'a {
+        let energy = storage.view::<EntityId, components::EnergyComponent>();
+        let hp = storage.view::<EntityId, components::HpComponent>();
+        let carry = storage.view::<EntityId, components::CarryComponent>();
+
+        fn in_bounds(value: Option<i64>, min: Option<i64>, max: Option<i64>) -> bool {
+            if min.is_none() && max.is_none() {
+                return true;
+            }
+            match value {
+                Some(value) => {
+                    min.map(|min| min <= value).unwrap_or(true)
+                        && max.map(|max| value <= max).unwrap_or(true)
+                }
+                None => false,
+            }
+        }
+
+        move |id| {
+            in_bounds(
+                energy.get(id).map(|e| e.energy as i64),
+                self.min_energy,
+                self.max_energy,
+            ) && in_bounds(hp.get(id).map(|h| h.hp as i64), self.min_hp, self.max_hp)
+                && in_bounds(
+                    carry.get(id).map(|c| c.carry() as i64),
+                    self.min_carry,
+                    self.max_carry,
+                )
+        }
+    }
+}
+
+/// Like `find_closest_by_range`, but only returns entities that also pass the `filter` Object.
+/// See [FindFilter](FindFilter) for the supported fields.
+pub fn find_closest_with_filter(
+    vm: &mut Vm<ScriptExecutionData>,
+    param: FindConstant,
+    filter: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("find_closest_with_filter");
+    trace!("find_closest_with_filter {:?}", param);
+
+    let filter = FindFilter::parse(filter)?;
+    let position = own_position(vm)?;
+    param.execute_with_filter(vm, position, filter)
+}
+
+/// Position of the entity executing the script
+fn own_position(vm: &Vm<ScriptExecutionData>) -> Result<WorldPosition, ExecutionError> {
+    let entity_id = vm.get_aux().entity_id;
+    match vm
+        .get_aux()
+        .storage()
+        .view::<EntityId, PositionComponent>()
+        .get(entity_id)
+    {
+        Some(p) => Ok(p.0),
+        None => {
+            warn!("{:?} has no PositionComponent", entity_id);
+            Err(ExecutionError::InvalidArgument { context: None })
+        }
+    }
+}
+
+fn parse_radius(radius: i64) -> Result<u32, ExecutionError> {
+    if radius < 0 {
+        return Err(ExecutionError::invalid_argument(format!(
+            "radius must not be negative, got {}",
+            radius
+        )));
+    }
+    Ok(radius.min(MAX_FIND_RADIUS as i64) as u32)
+}
+
+/// Returns an Object with the entities matching `param` within `radius` of the current entity,
+/// closest first. The ids are stored under the keys `"0"`..`"{length - 1}"`, and the number of
+/// entities under `"length"`.
+pub fn find_all_in_range(
+    vm: &mut Vm<ScriptExecutionData>,
+    param: FindConstant,
+    radius: i64,
+) -> Result<(), ExecutionError> {
+    profile!("find_all_in_range");
+    trace!("find_all_in_range {:?} {}", param, radius);
+
+    let radius = parse_radius(radius)?;
+    let position = own_position(vm)?;
+    let entities = {
+        let storage = vm.get_aux().storage();
+        let predicate = param.predicate(storage, vm.get_aux().user_id);
+        let mut entities = Vec::new();
+        room_entities(storage, position)?.query_range(
+            position.pos,
+            radius,
+            &mut |pos, EntityComponent(id)| {
+                if predicate(*id) {
+                    entities.push((position.pos.hex_distance(pos), *id));
+                }
+            },
+        );
+        entities.sort_unstable();
+        entities
+    };
+
+    let entities = entities
+        .into_iter()
+        .map(|(_, id)| {
+            let id: u64 = id.into();
+            Value::Integer(id as i64)
+        })
+        .collect::<Vec<_>>();
+    let result = make_list(vm, &entities)?;
+    vm.stack_push(result)?;
+    Ok(())
+}
+
+/// Returns the number of entities matching `param` within `radius` of the current entity
+pub fn count_in_range(
+    vm: &mut Vm<ScriptExecutionData>,
+    param: FindConstant,
+    radius: i64,
+) -> Result<(), ExecutionError> {
+    profile!("count_in_range");
+    trace!("count_in_range {:?} {}", param, radius);
+
+    let radius = parse_radius(radius)?;
+    let position = own_position(vm)?;
+    let count = {
+        let storage = vm.get_aux().storage();
+        let predicate = param.predicate(storage, vm.get_aux().user_id);
+        // `count_in_range_if` only checks the bounding box of the circle
+        room_entities(storage, position)?.count_in_range_if(
+            position.pos,
+            radius,
+            |pos, EntityComponent(id)| position.pos.hex_distance(pos) <= radius && predicate(*id),
+        )
+    };
+    vm.stack_push(count as i64)?;
+    Ok(())
+}
+
+fn room_entities(
+    storage: &World,
+    position: WorldPosition,
+) -> Result<&MortonTable<EntityComponent>, ExecutionError> {
+    storage
+        .view::<WorldPosition, EntityComponent>()
+        .reborrow()
+        .table
+        .at(position.room)
+        .ok_or_else(|| ExecutionError::InvalidArgument {
+            context: "find called on invalid room".to_string().into(),
+        })
+}
+
 fn find_closest_entity_impl<F>(
     storage: &World,
     position: WorldPosition,
@@ -324,4 +624,140 @@ mod tests {
             panic!("Expected pointer, got {:?}", res_id);
         }
     }
+
+    fn init_range_storage(center: WorldPosition, distances: &[i32]) -> (World, EntityId) {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        storage
+            .unsafe_view::<EntityId, PositionComponent>()
+            .insert(entity_id, PositionComponent(center));
+        storage
+            .unsafe_view::<WorldPosition, EntityComponent>()
+            .insert(center, EntityComponent(entity_id))
+            .unwrap();
+        for d in distances {
+            let id = storage.insert_entity();
+            let pos = WorldPosition {
+                room: center.room,
+                pos: center.pos + Axial::new(*d, 0),
+            };
+            storage
+                .unsafe_view::<WorldPosition, EntityComponent>()
+                .insert(pos, EntityComponent(id))
+                .unwrap();
+            storage
+                .unsafe_view::<EntityId, components::ResourceComponent>()
+                .insert(
+                    id,
+                    components::ResourceComponent(components::Resource::Energy),
+                );
+        }
+        (storage, entity_id)
+    }
+
+    #[test]
+    fn count_in_range_counts_within_radius() {
+        let center = WorldPosition {
+            room: Axial::new(0, 0),
+            pos: Axial::new(14, 14),
+        };
+        let (storage, entity_id) = init_range_storage(center, &[1, 2, 5, -2]);
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        count_in_range(&mut vm, FindConstant::Resource, 2).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Integer(3)));
+
+        count_in_range(&mut vm, FindConstant::Resource, 0).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Integer(0)));
+
+        count_in_range(&mut vm, FindConstant::Resource, -1).unwrap_err();
+    }
+
+    #[test]
+    fn find_all_in_range_returns_closest_first() {
+        let center = WorldPosition {
+            room: Axial::new(0, 0),
+            pos: Axial::new(14, 14),
+        };
+        let (storage, entity_id) = init_range_storage(center, &[3, 1, 5]);
+        let closest = storage
+            .view::<WorldPosition, EntityComponent>()
+            .get(WorldPosition {
+                room: center.room,
+                pos: center.pos + Axial::new(1, 0),
+            })
+            .unwrap()
+            .0;
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        find_all_in_range(&mut vm, FindConstant::Resource, 3).unwrap();
+        let result = match vm.stack_pop() {
+            Value::Object(table) => unsafe { &*table },
+            value => panic!("Expected an object, got {:?}", value),
+        };
+        let get = |key: &str| result.get_value(Handle::from_str(key).unwrap());
+
+        assert!(matches!(get("length"), Some(Value::Integer(2))));
+        let first: EntityId = get("0").unwrap().try_into().unwrap();
+        assert_eq!(first, closest);
+    }
+
+    #[test]
+    fn find_closest_with_filter_skips_entities_out_of_bounds() {
+        let center = WorldPosition {
+            room: Axial::new(0, 0),
+            pos: Axial::new(14, 14),
+        };
+        let (mut storage, entity_id) = init_range_storage(center, &[1, 3]);
+        let id_at = |storage: &World, d: i32| {
+            storage
+                .view::<WorldPosition, EntityComponent>()
+                .get(WorldPosition {
+                    room: center.room,
+                    pos: center.pos + Axial::new(d, 0),
+                })
+                .unwrap()
+                .0
+        };
+        let closest = id_at(&storage, 1);
+        let further = id_at(&storage, 3);
+        for (id, energy) in [(closest, 10), (further, 100)] {
+            storage
+                .unsafe_view::<EntityId, components::EnergyComponent>()
+                .insert(
+                    id,
+                    components::EnergyComponent {
+                        energy,
+                        energy_max: 100,
+                    },
+                );
+        }
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        let filter = FindFilter {
+            min_energy: Some(50),
+            ..Default::default()
+        };
+        FindConstant::Resource
+            .execute_with_filter(&mut vm, center, filter)
+            .unwrap();
+        let found: EntityId = vm.stack_pop().try_into().unwrap();
+        assert_eq!(found, further);
+
+        let filter = FindFilter {
+            min_hp: Some(1),
+            ..Default::default()
+        };
+        FindConstant::Resource
+            .execute_with_filter(&mut vm, center, filter)
+            .unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Nil));
+    }
 }
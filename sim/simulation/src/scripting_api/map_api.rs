@@ -0,0 +1,516 @@
+//! Read-only queries of the map
+//!
+//! Rooms are passed as Objects with the fields `rq` and `rr`, so positions can be used in
+//! their place.
+use super::*;
+use crate::components::{
+    game_config::GameConfig, PositionComponent, RoomComponent, RoomConnections, TerrainComponent,
+};
+use crate::indices::{ConfigKey, EntityId, RoomPosition};
+use crate::pathfinding::{self, PathFindingError};
+use crate::storage::views::{FromWorld, UnwrapView};
+use tracing::trace;
+
+/// Push the terrain at the given position as the discriminant of
+/// [TileTerrainType](crate::terrain::TileTerrainType), or `Nil` if the position is not on the map
+pub fn get_terrain(
+    vm: &mut Vm<ScriptExecutionData>,
+    point: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("get_terrain");
+    let pos = parse_world_pos(point)?;
+    trace!("get_terrain {:?}", pos);
+
+    let terrain = vm
+        .get_aux()
+        .storage()
+        .view::<WorldPosition, TerrainComponent>()
+        .get(pos)
+        .map(|TerrainComponent(t)| *t);
+    let value = match terrain {
+        Some(terrain) => Value::Integer(terrain as i64),
+        None => Value::Nil,
+    };
+    vm.stack_push(value)?;
+    Ok(())
+}
+
+/// Push a list of the exits of the room. Each exit holds the neighbouring room in `rq`, `rr`
+/// and the section of the room's edge that is bridged in `offsetStart`, `offsetEnd`.
+///
+/// Pushes `Nil` if the room does not exist.
+pub fn get_room_exits(
+    vm: &mut Vm<ScriptExecutionData>,
+    room: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("get_room_exits");
+    let room = parse_room(room)?;
+    trace!("get_room_exits {:?}", room);
+
+    let connections = vm
+        .get_aux()
+        .storage()
+        .view::<Axial, RoomConnections>()
+        .at(room)
+        .cloned();
+    let connections = match connections {
+        Some(c) => c,
+        None => {
+            vm.stack_push(Value::Nil)?;
+            return Ok(());
+        }
+    };
+    let mut exits = Vec::with_capacity(6);
+    for connection in connections.0.iter().flatten() {
+        let neighbour = room + connection.direction;
+        let exit = make_object(
+            vm,
+            &[
+                ("rq", Value::Integer(neighbour.q as i64)),
+                ("rr", Value::Integer(neighbour.r as i64)),
+                (
+                    "offsetStart",
+                    Value::Integer(connection.offset_start as i64),
+                ),
+                ("offsetEnd", Value::Integer(connection.offset_end as i64)),
+            ],
+        )?;
+        exits.push(exit);
+    }
+    let exits = make_list(vm, &exits)?;
+    vm.stack_push(exits)?;
+    Ok(())
+}
+
+/// Push a list of the rooms that exist next to the given room, whether they are connected to
+/// it or not
+pub fn list_neighbour_rooms(
+    vm: &mut Vm<ScriptExecutionData>,
+    room: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("list_neighbour_rooms");
+    let room = parse_room(room)?;
+    trace!("list_neighbour_rooms {:?}", room);
+
+    let neighbours = {
+        let rooms = vm.get_aux().storage().view::<Axial, RoomComponent>();
+        room.hex_neighbours()
+            .iter()
+            .copied()
+            .filter(|neighbour| rooms.contains_key(*neighbour))
+            .collect::<Vec<_>>()
+    };
+    let neighbours = neighbours
+        .into_iter()
+        .map(|neighbour| room_to_object(vm, neighbour))
+        .collect::<Result<Vec<_>, _>>()?;
+    let neighbours = make_list(vm, &neighbours)?;
+    vm.stack_push(neighbours)?;
+    Ok(())
+}
+
+/// Push the room the entity is in, or `Nil` if it has no position
+pub fn room_of(
+    vm: &mut Vm<ScriptExecutionData>,
+    entity_id: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("room_of");
+    let pos = vm
+        .get_aux()
+        .storage()
+        .view::<EntityId, PositionComponent>()
+        .get(entity_id)
+        .map(|PositionComponent(pos)| *pos);
+    let value = match pos {
+        Some(pos) => room_to_object(vm, pos.room)?,
+        None => Value::Nil,
+    };
+    vm.stack_push(value)?;
+    Ok(())
+}
+
+/// Find a path from `from` to `to`, in walking order, charging the steps taken to the
+/// native instructions of the script.
+///
+/// Like `move_to_position`, the path ends next to `to`. If `to` is in another room, the path
+/// leads to the exit of `from`'s room towards it.
+fn find_path_impl(
+    vm: &mut Vm<ScriptExecutionData>,
+    from: WorldPosition,
+    to: WorldPosition,
+) -> Result<Option<Vec<RoomPosition>>, ExecutionError> {
+    let storage = vm.get_aux().storage();
+    let conf = UnwrapView::<ConfigKey, GameConfig>::from_world(storage);
+    let steps_per_instr = conf.path_finding_steps_per_instruction.max(1) as u64;
+    let aux = vm.get_aux();
+    let remaining = aux.native_instr_limit.saturating_sub(aux.native_instr);
+    if remaining == 0 {
+        return Err(ExecutionError::TaskFailure(
+            "Script is out of instructions for path finding".to_string(),
+        ));
+    }
+    let max_steps =
+        (conf.path_finding_limit as u64).min(remaining.saturating_mul(steps_per_instr)) as u32;
+
+    let mut path = Vec::with_capacity(max_steps as usize);
+    let mut next_room = None;
+    let (steps, path) = match pathfinding::find_path(
+        from,
+        to,
+        1,
+        FromWorld::from_world(storage),
+        max_steps,
+        &mut path,
+        &mut next_room,
+    ) {
+        Ok(remaining) => {
+            path.reverse();
+            (max_steps - remaining, Some(path))
+        }
+        Err(err @ PathFindingError::Timeout) | Err(err @ PathFindingError::Unreachable) => {
+            trace!("find_path failed {:?}", err);
+            (max_steps, None)
+        }
+        Err(err) => {
+            trace!("find_path failed {:?}", err);
+            (0, None)
+        }
+    };
+    let cost = (steps as u64).div_ceil(steps_per_instr);
+    trace!(
+        "find_path took {} steps, costing {} instructions",
+        steps,
+        cost
+    );
+    vm.get_aux_mut().native_instr += cost;
+    Ok(path)
+}
+
+/// Push the list of positions leading from `from` to `to`, or `Nil` if there is no path
+pub fn find_path(
+    vm: &mut Vm<ScriptExecutionData>,
+    from: &FieldTable,
+    to: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("find_path");
+    let from = parse_world_pos(from)?;
+    let to = parse_world_pos(to)?;
+    trace!("find_path {:?} {:?}", from, to);
+
+    let path = match find_path_impl(vm, from, to)? {
+        Some(path) => path,
+        None => {
+            vm.stack_push(Value::Nil)?;
+            return Ok(());
+        }
+    };
+    let path = path
+        .into_iter()
+        .map(|RoomPosition(pos)| {
+            world_pos_to_object(
+                vm,
+                WorldPosition {
+                    room: from.room,
+                    pos,
+                },
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let path = make_list(vm, &path)?;
+    vm.stack_push(path)?;
+    Ok(())
+}
+
+/// Push the number of steps it takes to walk from `from` to `to`, or `Nil` if there is no path
+pub fn path_distance(
+    vm: &mut Vm<ScriptExecutionData>,
+    from: &FieldTable,
+    to: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("path_distance");
+    let from = parse_world_pos(from)?;
+    let to = parse_world_pos(to)?;
+    trace!("path_distance {:?} {:?}", from, to);
+
+    let value = match find_path_impl(vm, from, to)? {
+        Some(path) => Value::Integer(path.len() as i64),
+        None => Value::Nil,
+    };
+    vm.stack_push(value)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{EntityComponent, RoomConnection};
+    use crate::indices::Room;
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+    use crate::terrain::TileTerrainType;
+    use cao_lang::compiler::{CallNode, IntegerNode, LaneNode};
+
+    fn get_field(value: Value, key: &str) -> Option<Value> {
+        match value {
+            Value::Object(table) => unsafe { (*table).get_value(Handle::from_str(key).unwrap()) },
+            _ => panic!("Expected an object, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_room_exits_and_neighbours() {
+        let mut storage = World::new();
+        let room = Axial::new(1, 1);
+        let direction = Axial::NEIGHBOURS[2];
+
+        let mut connections = RoomConnections::default();
+        connections.0[2] = Some(RoomConnection {
+            direction,
+            offset_start: 1,
+            offset_end: 3,
+        });
+        query!(
+            mutate
+            storage
+            {
+                Axial, RoomConnections, .insert(room, connections).unwrap();
+                Axial, RoomComponent, .insert(room, RoomComponent::default()).unwrap();
+                Axial, RoomComponent, .insert(room + direction, RoomComponent::default()).unwrap();
+                Axial, RoomComponent, .insert(room + Axial::NEIGHBOURS[4], RoomComponent::default()).unwrap();
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            Default::default(),
+            None,
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+
+        let room_obj = room_to_object(&mut vm, room).unwrap();
+        let room_table = match room_obj {
+            Value::Object(t) => unsafe { &*t },
+            _ => unreachable!(),
+        };
+
+        get_room_exits(&mut vm, room_table).unwrap();
+        let exits = vm.stack_pop();
+        assert!(matches!(
+            get_field(exits, "length"),
+            Some(Value::Integer(1))
+        ));
+        let exit = get_field(exits, "0").unwrap();
+        let neighbour = room + direction;
+        assert!(
+            matches!(get_field(exit, "rq"), Some(Value::Integer(q)) if q == neighbour.q as i64)
+        );
+        assert!(
+            matches!(get_field(exit, "rr"), Some(Value::Integer(r)) if r == neighbour.r as i64)
+        );
+        assert!(matches!(
+            get_field(exit, "offsetEnd"),
+            Some(Value::Integer(3))
+        ));
+
+        // the second neighbour exists, but is not connected
+        list_neighbour_rooms(&mut vm, room_table).unwrap();
+        let neighbours = vm.stack_pop();
+        assert!(matches!(
+            get_field(neighbours, "length"),
+            Some(Value::Integer(2))
+        ));
+    }
+
+    #[test]
+    fn test_get_terrain_and_room_of() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let pos = WorldPosition {
+            room: Axial::new(0, 0),
+            pos: Axial::new(2, 3),
+        };
+        query!(
+            mutate
+            storage
+            {
+                EntityId, PositionComponent, .insert(entity_id, PositionComponent(pos));
+                WorldPosition, TerrainComponent,
+                    .extend_rooms([Room(pos.room)].iter().cloned())
+                    .unwrap();
+                WorldPosition, TerrainComponent,
+                    .iter_rooms_mut().for_each(|(_, room)| room.resize(3));
+                WorldPosition, TerrainComponent,
+                    .extend_from_slice(&mut [(pos, TerrainComponent(TileTerrainType::Wall))])
+                    .unwrap();
+            }
+        );
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        room_of(&mut vm, entity_id).unwrap();
+        let room = vm.stack_pop();
+        assert!(matches!(get_field(room, "rq"), Some(Value::Integer(0))));
+
+        let pos_obj = match world_pos_to_object(&mut vm, pos).unwrap() {
+            Value::Object(t) => unsafe { &*t },
+            _ => unreachable!(),
+        };
+        get_terrain(&mut vm, pos_obj).unwrap();
+        let terrain = vm.stack_pop();
+        assert!(
+            matches!(terrain, Value::Integer(t) if t == TileTerrainType::Wall as i64),
+            "{:?}",
+            terrain
+        );
+
+        room_of(&mut vm, EntityId::new(42, 0)).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Nil));
+    }
+
+    /// A single room of radius 3 with plain terrain, except for `walls`
+    fn init_path_finding_world(room: Room, walls: &[Axial]) -> World {
+        let mut storage = World::new();
+        let mut terrain = crate::prelude::Hexagon::from_radius(3)
+            .iter_points()
+            .map(|pos| {
+                let terrain = if walls.contains(&pos) {
+                    TileTerrainType::Wall
+                } else {
+                    TileTerrainType::Plain
+                };
+                (
+                    WorldPosition { room: room.0, pos },
+                    TerrainComponent(terrain),
+                )
+            })
+            .collect::<Vec<_>>();
+        query!(
+            mutate
+            storage
+            {
+                WorldPosition, EntityComponent,
+                    .extend_rooms([room].iter().cloned())
+                    .unwrap();
+                WorldPosition, TerrainComponent,
+                    .extend_rooms([room].iter().cloned())
+                    .unwrap();
+                WorldPosition, TerrainComponent,
+                    .iter_rooms_mut().for_each(|(_, room)| room.resize(3));
+                WorldPosition, TerrainComponent,
+                    .extend_from_slice(&mut terrain)
+                    .unwrap();
+            }
+        );
+        storage
+    }
+
+    #[test]
+    fn test_find_path_charges_instructions() {
+        let room = Room(Axial::new(0, 0));
+        let from = WorldPosition {
+            room: room.0,
+            pos: Axial::new(3, 1),
+        };
+        let to = WorldPosition {
+            room: room.0,
+            pos: Axial::new(3, 5),
+        };
+        let storage = init_path_finding_world(room, &[]);
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            Default::default(),
+            None,
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+        vm.max_instr = 1000;
+        vm.get_aux_mut().native_instr_limit = 1000;
+
+        let as_table = |value: Value| match value {
+            Value::Object(t) => unsafe { &*t },
+            value => panic!("Expected an object, got {:?}", value),
+        };
+        let from_obj = as_table(world_pos_to_object(&mut vm, from).unwrap());
+        let to_obj = as_table(world_pos_to_object(&mut vm, to).unwrap());
+
+        path_distance(&mut vm, from_obj, to_obj).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Integer(3)));
+        assert!(vm.get_aux().native_instr > 0);
+
+        find_path(&mut vm, from_obj, to_obj).unwrap();
+        let path = vm.stack_pop();
+        assert!(matches!(get_field(path, "length"), Some(Value::Integer(3))));
+        let last = as_table(get_field(path, "2").unwrap());
+        let last = parse_world_pos(last).unwrap();
+        assert_eq!(last.pos.hex_distance(to.pos), 1);
+    }
+
+    #[test]
+    fn test_find_path_calls_hit_the_script_limit() {
+        let room = Room(Axial::new(0, 0));
+        let from = WorldPosition {
+            room: room.0,
+            pos: Axial::new(3, 1),
+        };
+        let to = WorldPosition {
+            room: room.0,
+            pos: Axial::new(3, 5),
+        };
+        // wall off the target, so every call explores the whole room
+        let walls = (0..6).map(|i| to.pos.hex_neighbour(i)).collect::<Vec<_>>();
+        let storage = init_path_finding_world(room, walls.as_slice());
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            Default::default(),
+            None,
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+        vm.max_instr = 1000;
+        vm.get_aux_mut().native_instr_limit = 1000;
+        vm.register_function("find_path", into_f2(find_path));
+        vm.register_function(
+            "targets",
+            move |vm: &mut Vm<ScriptExecutionData>| -> Result<(), ExecutionError> {
+                let from = world_pos_to_object(vm, from)?;
+                let to = world_pos_to_object(vm, to)?;
+                vm.stack_push(from)?;
+                vm.stack_push(to)?;
+                Ok(())
+            },
+        );
+
+        let program = CaoIr {
+            lanes: vec![
+                Lane::default().with_name("Main").with_cards(vec![
+                    Card::ScalarInt(IntegerNode(100)),
+                    Card::Repeat(LaneNode::LaneName("Loop".to_string())),
+                ]),
+                Lane::default()
+                    .with_name("Loop")
+                    .with_arg("i")
+                    .with_cards(vec![
+                        Card::CallNative(Box::new(CallNode(InputString::from("targets").unwrap()))),
+                        Card::CallNative(Box::new(CallNode(
+                            InputString::from("find_path").unwrap(),
+                        ))),
+                        Card::Pop,
+                    ]),
+            ],
+        };
+        let program = compile(&program, None).unwrap();
+
+        let err = vm.run(&program).unwrap_err();
+        assert!(matches!(err, ExecutionError::TaskFailure(_)), "{:?}", err);
+        assert_eq!(vm.get_aux().native_instr, 1000);
+    }
+}
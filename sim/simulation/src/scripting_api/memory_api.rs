@@ -0,0 +1,244 @@
+//! Memory that scripts keep between ticks
+//!
+//! Every entity and every user has a memory of key-value pairs, limited in size by the
+//! [GameConfig](crate::components::game_config::GameConfig). Changes are written back after
+//! the script succeeds; the changes of failed scripts are discarded.
+use super::*;
+use crate::components::{game_config::GameConfig, MemoryValue};
+use crate::indices::ConfigKey;
+use crate::storage::views::{FromWorld, UnwrapView};
+use cao_lang::StrPointer;
+use tracing::trace;
+
+fn read_key(key: StrPointer) -> Result<String, ExecutionError> {
+    unsafe { key.get_str() }
+        .map(|key| key.to_owned())
+        .ok_or_else(|| ExecutionError::invalid_argument("memory key must be a string".to_owned()))
+}
+
+/// `Nil` is converted to `None`, which removes the key when set.
+/// Returns `Err` for values that can not be stored.
+pub(super) fn to_memory_value(value: Value) -> Result<Option<MemoryValue>, Value> {
+    let value = match value {
+        Value::Nil => return Ok(None),
+        Value::Integer(i) => MemoryValue::Integer(i),
+        Value::Floating(f) => MemoryValue::Floating(f),
+        Value::String(s) => match unsafe { s.get_str() } {
+            Some(s) => MemoryValue::Text(s.to_owned()),
+            None => return Err(value),
+        },
+        Value::Object(_) => return Err(value),
+    };
+    Ok(Some(value))
+}
+
+/// Inverse of [to_memory_value](to_memory_value)
+pub(super) fn from_memory_value(
+    vm: &mut Vm<ScriptExecutionData>,
+    value: Option<MemoryValue>,
+) -> Result<Value, ExecutionError> {
+    let value = match value {
+        None => Value::Nil,
+        Some(MemoryValue::Integer(i)) => Value::Integer(i),
+        Some(MemoryValue::Floating(f)) => Value::Floating(f),
+        Some(MemoryValue::Text(s)) => Value::String(init_string(vm, s.as_str())?),
+    };
+    Ok(value)
+}
+
+fn push_memory_value(
+    vm: &mut Vm<ScriptExecutionData>,
+    value: Option<MemoryValue>,
+) -> Result<(), ExecutionError> {
+    let value = from_memory_value(vm, value)?;
+    vm.stack_push(value)?;
+    Ok(())
+}
+
+fn memory_limits(vm: &Vm<ScriptExecutionData>) -> (usize, usize) {
+    let conf = UnwrapView::<ConfigKey, GameConfig>::from_world(vm.get_aux().storage());
+    (conf.entity_memory_limit, conf.user_memory_limit)
+}
+
+/// Push the value stored under `key` in the memory of the entity, or `Nil`
+pub fn get_memory(vm: &mut Vm<ScriptExecutionData>, key: StrPointer) -> Result<(), ExecutionError> {
+    profile!("get_memory");
+    let key = read_key(key)?;
+    trace!("get_memory {}", key);
+    let value = vm
+        .get_aux()
+        .memory()
+        .and_then(|memory| memory.0.get(&key).cloned());
+    push_memory_value(vm, value)
+}
+
+/// Store `value` under `key` in the memory of the entity. `Nil` removes the key.
+///
+/// Returns `Full` if the memory would exceed its limit.
+pub fn set_memory(
+    vm: &mut Vm<ScriptExecutionData>,
+    key: StrPointer,
+    value: Value,
+) -> Result<(), ExecutionError> {
+    profile!("set_memory");
+    let key = read_key(key)?;
+    trace!("set_memory {} {:?}", key, value);
+    let value = match to_memory_value(value) {
+        Ok(v) => v,
+        Err(_) => {
+            vm.stack_push(OperationResult::InvalidInput)?;
+            return Ok(());
+        }
+    };
+    let (limit, _) = memory_limits(vm);
+    let aux = vm.get_aux_mut();
+    let res = if aux.memory_mut().set(key, value, limit) {
+        aux.memory_changed = true;
+        OperationResult::Ok
+    } else {
+        OperationResult::Full
+    };
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+/// Push the value stored under `key` in the memory of the owner of the entity, or `Nil`
+pub fn get_user_memory(
+    vm: &mut Vm<ScriptExecutionData>,
+    key: StrPointer,
+) -> Result<(), ExecutionError> {
+    profile!("get_user_memory");
+    let key = read_key(key)?;
+    trace!("get_user_memory {}", key);
+    let value = vm
+        .get_aux_mut()
+        .user_memory_mut()
+        .and_then(|memory| memory.0.get(&key).cloned());
+    push_memory_value(vm, value)
+}
+
+/// Store `value` under `key` in the memory of the owner of the entity. `Nil` removes the key.
+///
+/// Scripts see their own changes immediately, other scripts of the user in the next tick.
+/// Returns `Full` if the memory would exceed its limit, `NotOwner` for unowned entities.
+pub fn set_user_memory(
+    vm: &mut Vm<ScriptExecutionData>,
+    key: StrPointer,
+    value: Value,
+) -> Result<(), ExecutionError> {
+    profile!("set_user_memory");
+    let key = read_key(key)?;
+    trace!("set_user_memory {} {:?}", key, value);
+    let value = match to_memory_value(value) {
+        Ok(v) => v,
+        Err(_) => {
+            vm.stack_push(OperationResult::InvalidInput)?;
+            return Ok(());
+        }
+    };
+    let (_, limit) = memory_limits(vm);
+    let aux = vm.get_aux_mut();
+    let stored = aux
+        .user_memory_mut()
+        .map(|memory| memory.set(key.clone(), value.clone(), limit));
+    let res = match stored {
+        None => OperationResult::NotOwner,
+        Some(true) => {
+            aux.user_memory_changes.push((key, value));
+            OperationResult::Ok
+        }
+        Some(false) => OperationResult::Full,
+    };
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::ScriptMemory;
+    use crate::indices::{EntityId, UserId};
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+
+    fn pop_result(vm: &mut Vm<ScriptExecutionData>) -> OperationResult {
+        vm.stack_pop().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_memory_is_loaded_and_written_back() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let mut memory = ScriptMemory::default();
+        memory
+            .0
+            .insert("counter".to_owned(), MemoryValue::Integer(41));
+        query!(
+            mutate
+            storage
+            {
+                EntityId, ScriptMemory, .insert(entity_id, memory);
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            entity_id,
+            Some(user_id),
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+        vm.get_aux_mut().reset(entity_id, Some(user_id));
+        let key = |vm: &mut Vm<ScriptExecutionData>| init_string(vm, "counter").unwrap();
+
+        let k = key(&mut vm);
+        get_memory(&mut vm, k).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Integer(41)));
+
+        let k = key(&mut vm);
+        set_memory(&mut vm, k, Value::Integer(42)).unwrap();
+        assert_eq!(pop_result(&mut vm), OperationResult::Ok);
+        let k = key(&mut vm);
+        set_user_memory(&mut vm, k, Value::Floating(1.5)).unwrap();
+        assert_eq!(pop_result(&mut vm), OperationResult::Ok);
+        let k = key(&mut vm);
+        get_user_memory(&mut vm, k).unwrap();
+        assert!(matches!(vm.stack_pop(), Value::Floating(f) if f == 1.5));
+
+        let intent = vm.get_aux_mut().take_memory_intent().unwrap();
+        assert_eq!(
+            intent.entity_memory.unwrap().0.get("counter"),
+            Some(&MemoryValue::Integer(42))
+        );
+        assert_eq!(
+            intent.user_memory_changes,
+            vec![("counter".to_owned(), Some(MemoryValue::Floating(1.5)))]
+        );
+    }
+
+    #[test]
+    fn test_set_memory_over_the_limit_is_full() {
+        let mut storage = World::new();
+        storage
+            .unsafe_view::<ConfigKey, GameConfig>()
+            .unwrap_mut()
+            .entity_memory_limit = 8;
+        let entity_id = storage.insert_entity();
+
+        let data =
+            ScriptExecutionData::new(&storage, Default::default(), entity_id, None, get_alloc());
+        let mut vm = Vm::new(data).unwrap();
+
+        let k = init_string(&mut vm, "too long").unwrap();
+        set_memory(&mut vm, k, Value::Integer(1)).unwrap();
+        assert_eq!(pop_result(&mut vm), OperationResult::Full);
+
+        let k = init_string(&mut vm, "k").unwrap();
+        set_user_memory(&mut vm, k, Value::Integer(1)).unwrap();
+        assert_eq!(pop_result(&mut vm), OperationResult::NotOwner);
+        assert!(vm.get_aux_mut().take_memory_intent().is_none());
+    }
+}
@@ -0,0 +1,213 @@
+//! Messages between the scripts of a user
+//!
+//! Messages are sent to a named channel of the user or to one of the user's entities, and can
+//! be read during the next tick. Payloads are integers, floats or texts.
+use super::memory_api::{from_memory_value, to_memory_value};
+use super::*;
+use crate::components::{game_config::GameConfig, Inbox, ScriptMessage, UserChannels};
+use crate::indices::{ConfigKey, EntityId, UserId};
+use crate::intents::{MessageTarget, MessagesIntent};
+use crate::storage::views::{FromWorld, UnwrapView};
+use cao_lang::StrPointer;
+use tracing::trace;
+
+fn send(
+    vm: &mut Vm<ScriptExecutionData>,
+    target: MessageTarget,
+    payload: Value,
+) -> Result<(), ExecutionError> {
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let payload = match to_memory_value(payload) {
+        Ok(Some(payload)) => payload,
+        Ok(None) | Err(_) => {
+            vm.stack_push(OperationResult::InvalidInput)?;
+            return Ok(());
+        }
+    };
+    let limit = UnwrapView::<ConfigKey, GameConfig>::from_world(aux.storage()).message_size_limit;
+    if target.size() + payload.size() > limit {
+        vm.stack_push(OperationResult::Full)?;
+        return Ok(());
+    }
+    let sender = aux.entity_id;
+    vm.get_aux_mut()
+        .intents
+        .messages_intent
+        .get_or_insert_with(|| MessagesIntent {
+            sender,
+            user_id,
+            messages: Vec::new(),
+        })
+        .messages
+        .push((target, payload));
+    vm.stack_push(OperationResult::Ok)?;
+    Ok(())
+}
+
+/// Post a message to the named channel of the user
+pub fn send_message(
+    vm: &mut Vm<ScriptExecutionData>,
+    channel: StrPointer,
+    payload: Value,
+) -> Result<(), ExecutionError> {
+    profile!("send_message");
+    let channel = unsafe { channel.get_str() }
+        .ok_or_else(|| ExecutionError::invalid_argument("channel must be a string".to_owned()))?;
+    trace!("send_message {} {:?}", channel, payload);
+    send(vm, MessageTarget::Channel(channel.to_owned()), payload)
+}
+
+/// Send a message to an entity of the user
+pub fn send_message_to(
+    vm: &mut Vm<ScriptExecutionData>,
+    target: EntityId,
+    payload: Value,
+) -> Result<(), ExecutionError> {
+    profile!("send_message_to");
+    trace!("send_message_to {:?} {:?}", target, payload);
+    send(vm, MessageTarget::Entity(target), payload)
+}
+
+/// Push a list of messages as Objects with the fields `sender` and `payload`
+fn push_messages(
+    vm: &mut Vm<ScriptExecutionData>,
+    messages: Vec<ScriptMessage>,
+) -> Result<(), ExecutionError> {
+    let messages = messages
+        .into_iter()
+        .map(|ScriptMessage { sender, payload }| {
+            let sender: u64 = sender.into();
+            let payload = from_memory_value(vm, Some(payload))?;
+            make_object(
+                vm,
+                &[
+                    ("sender", Value::Integer(sender as i64)),
+                    ("payload", payload),
+                ],
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let messages = make_list(vm, &messages)?;
+    vm.stack_push(messages)?;
+    Ok(())
+}
+
+/// Push the messages posted to the named channel of the user in the last tick
+pub fn read_channel(
+    vm: &mut Vm<ScriptExecutionData>,
+    channel: StrPointer,
+) -> Result<(), ExecutionError> {
+    profile!("read_channel");
+    let channel = unsafe { channel.get_str() }
+        .ok_or_else(|| ExecutionError::invalid_argument("channel must be a string".to_owned()))?;
+    trace!("read_channel {}", channel);
+    let aux = vm.get_aux();
+    let messages = aux
+        .user_id
+        .and_then(|user_id| {
+            aux.storage()
+                .view::<UserId, UserChannels>()
+                .get(user_id)
+                .and_then(|channels| channels.0.get(channel).cloned())
+        })
+        .unwrap_or_default();
+    push_messages(vm, messages)
+}
+
+/// Push the messages sent to the entity in the last tick
+pub fn read_inbox(vm: &mut Vm<ScriptExecutionData>) -> Result<(), ExecutionError> {
+    profile!("read_inbox");
+    let aux = vm.get_aux();
+    let messages = aux
+        .storage()
+        .view::<EntityId, Inbox>()
+        .get(aux.entity_id)
+        .map(|inbox| inbox.0.clone())
+        .unwrap_or_default();
+    push_messages(vm, messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::MemoryValue;
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+
+    #[test]
+    fn test_send_and_read_messages() {
+        let mut storage = World::new();
+        let entity_id = storage.insert_entity();
+        let sender = storage.insert_entity();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let mut channels = UserChannels::default();
+        channels.0.insert(
+            "defense".to_owned(),
+            vec![ScriptMessage {
+                sender,
+                payload: MemoryValue::Integer(42),
+            }],
+        );
+        query!(
+            mutate
+            storage
+            {
+                UserId, UserChannels, .insert(user_id, channels);
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            entity_id,
+            Some(user_id),
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+
+        let channel = init_string(&mut vm, "defense").unwrap();
+        read_channel(&mut vm, channel).unwrap();
+        let messages = match vm.stack_pop() {
+            Value::Object(t) => unsafe { &*t },
+            value => panic!("Expected an object, got {:?}", value),
+        };
+        let get = |table: &FieldTable, key: &str| table.get_value(Handle::from_str(key).unwrap());
+        assert!(matches!(get(messages, "length"), Some(Value::Integer(1))));
+        let message = match get(messages, "0") {
+            Some(Value::Object(t)) => unsafe { &*t },
+            value => panic!("Expected an object, got {:?}", value),
+        };
+        assert!(matches!(get(message, "payload"), Some(Value::Integer(42))));
+        let from: EntityId = get(message, "sender").unwrap().try_into().unwrap();
+        assert_eq!(from, sender);
+
+        let channel = init_string(&mut vm, "defense").unwrap();
+        send_message(&mut vm, channel, Value::Integer(1)).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Ok);
+        send_message_to(&mut vm, sender, Value::Floating(0.5)).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Ok);
+
+        let intent = vm.get_aux().intents.messages_intent.clone().unwrap();
+        assert_eq!(intent.sender, entity_id);
+        assert_eq!(
+            intent.messages,
+            vec![
+                (
+                    MessageTarget::Channel("defense".to_owned()),
+                    MemoryValue::Integer(1)
+                ),
+                (MessageTarget::Entity(sender), MemoryValue::Floating(0.5)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Script-controlled spawning
+//!
+//! Scripts queue bots in the spawns of their user. The body of the bot is paid for from the
+//! energy of the spawn when the bot starts spawning.
+use super::*;
+use crate::components::BotBody;
+use crate::indices::{EntityId, ScriptId};
+use crate::intents::{
+    check_continuous_spawn_intent, check_spawn_intent, check_spawn_script_intent,
+    ContinuousSpawnIntent, SpawnIntent, SpawnScriptIntent,
+};
+use crate::storage::views::FromWorld;
+use std::convert::TryFrom;
+use tracing::trace;
+
+/// Reads a BotBody from the fields `hp`, `carry`, `melee`, `ranged`, `armor`, `heal` and
+/// `decayInterval`.
+/// Missing or `Nil` fields take the value of the default body.
+pub fn parse_bot_body(table: &FieldTable) -> Result<BotBody, ExecutionError> {
+    fn get<T: TryFrom<i64>>(
+        table: &FieldTable,
+        key: &str,
+        default: T,
+    ) -> Result<T, ExecutionError> {
+        match table.get_value(Handle::from_str(key).unwrap()) {
+            None | Some(Value::Nil) => Ok(default),
+            Some(Value::Integer(i)) => T::try_from(i).map_err(|_| {
+                ExecutionError::invalid_argument(format!(
+                    "body field {} is out of range: {}",
+                    key, i
+                ))
+            }),
+            Some(value) => Err(ExecutionError::invalid_argument(format!(
+                "body field {} must be an integer, got {:?}",
+                key, value
+            ))),
+        }
+    }
+    let default = BotBody::default();
+    Ok(BotBody {
+        hp: get(table, "hp", default.hp)?,
+        carry: get(table, "carry", default.carry)?,
+        melee: get(table, "melee", default.melee)?,
+        ranged: get(table, "ranged", default.ranged)?,
+        armor: get(table, "armor", default.armor)?,
+        heal: get(table, "heal", default.heal)?,
+        decay_interval: get(table, "decayInterval", default.decay_interval)?,
+    })
+}
+
+/// Queue a bot with the given body in the spawn
+pub fn spawn_bot(
+    vm: &mut Vm<ScriptExecutionData>,
+    spawn_id: EntityId,
+    body: &FieldTable,
+) -> Result<(), ExecutionError> {
+    profile!("spawn_bot");
+    let body = parse_bot_body(body)?;
+    trace!("spawn_bot {:?} {:?}", spawn_id, body);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = SpawnIntent {
+        spawn_id,
+        owner_id: Some(user_id),
+        body,
+    };
+    let res = check_spawn_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.spawn_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+/// Turn continuous spawning of the spawn on (non-zero) or off (zero)
+pub fn set_continuous_spawning(
+    vm: &mut Vm<ScriptExecutionData>,
+    spawn_id: EntityId,
+    enabled: i64,
+) -> Result<(), ExecutionError> {
+    profile!("set_continuous_spawning");
+    trace!("set_continuous_spawning {:?} {}", spawn_id, enabled);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = ContinuousSpawnIntent {
+        spawn_id,
+        enabled: enabled != 0,
+    };
+    let res = check_continuous_spawn_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.continuous_spawn_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+/// Set the script the bots spawned by the current entity run, given the id of the script as
+/// text. `Nil` restores the default script of the owner.
+pub fn set_spawn_script(
+    vm: &mut Vm<ScriptExecutionData>,
+    script_id: Value,
+) -> Result<(), ExecutionError> {
+    profile!("set_spawn_script");
+    trace!("set_spawn_script {:?}", script_id);
+
+    let script_id = match script_id {
+        Value::Nil => None,
+        Value::String(s) => {
+            let s = unsafe { s.get_str() }.ok_or_else(|| {
+                ExecutionError::invalid_argument("script id must be a string".to_owned())
+            })?;
+            match uuid::Uuid::parse_str(s) {
+                Ok(id) => Some(ScriptId(id)),
+                Err(_) => {
+                    vm.stack_push(OperationResult::InvalidInput)?;
+                    return Ok(());
+                }
+            }
+        }
+        _ => {
+            return Err(ExecutionError::invalid_argument(
+                "script id must be a string or Nil".to_owned(),
+            ))
+        }
+    };
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = SpawnScriptIntent {
+        spawn_id: aux.entity_id,
+        script_id,
+    };
+    let res = check_spawn_script_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.spawn_script_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{EnergyComponent, OwnedEntity, SpawnQueueComponent};
+    use crate::indices::UserId;
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+
+    #[test]
+    fn test_spawn_bot_checks_the_cost_of_the_body() {
+        let mut storage = World::new();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let spawn_id = storage.insert_entity();
+        query!(
+            mutate
+            storage
+            {
+                EntityId, SpawnQueueComponent, .insert(spawn_id, Default::default());
+                EntityId, OwnedEntity, .insert(spawn_id, OwnedEntity { owner_id: user_id });
+                EntityId, EnergyComponent, .insert(
+                    spawn_id,
+                    EnergyComponent {
+                        energy: 0,
+                        energy_max: 500,
+                    }
+                );
+            }
+        );
+        let bot_id = storage.insert_entity();
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            bot_id,
+            Some(user_id),
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+
+        // more than the 500 energy the spawn can store
+        let body = make_object(&mut vm, &[("melee", Value::Integer(200))]).unwrap();
+        let body = match body {
+            Value::Object(t) => unsafe { &*t },
+            _ => unreachable!(),
+        };
+        spawn_bot(&mut vm, spawn_id, body).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::InvalidInput);
+        assert!(vm.get_aux().intents.spawn_intent.is_none());
+
+        let body = make_object(
+            &mut vm,
+            &[
+                ("hp", Value::Integer(50)),
+                ("carry", Value::Integer(0)),
+                ("melee", Value::Integer(100)),
+            ],
+        )
+        .unwrap();
+        let body = match body {
+            Value::Object(t) => unsafe { &*t },
+            _ => unreachable!(),
+        };
+        spawn_bot(&mut vm, spawn_id, body).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Ok);
+        let intent = vm.get_aux().intents.spawn_intent.clone().unwrap();
+        assert_eq!(
+            intent.body,
+            BotBody {
+                hp: 50,
+                carry: 0,
+                melee: 100,
+                ranged: 0,
+                armor: 0,
+                heal: 0,
+                decay_interval: 10
+            }
+        );
+
+        set_continuous_spawning(&mut vm, bot_id, 0).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::NotOwner);
+    }
+}
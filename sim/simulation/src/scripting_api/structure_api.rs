@@ -0,0 +1,104 @@
+//! Actions of structures
+//!
+//! The current entity of the script is the structure performing the action.
+use super::*;
+use crate::indices::EntityId;
+use crate::intents::{check_energy_transfer_intent, EnergyTransferIntent};
+use crate::storage::views::FromWorld;
+use tracing::trace;
+
+/// Transfer as much energy of the structure to the target as the target can hold
+pub fn transfer_energy(
+    vm: &mut Vm<ScriptExecutionData>,
+    target: EntityId,
+) -> Result<(), ExecutionError> {
+    profile!("transfer_energy");
+    trace!("transfer_energy {:?}", target);
+
+    let aux = vm.get_aux();
+    let user_id = match aux.user_id {
+        Some(user_id) => user_id,
+        None => {
+            vm.stack_push(OperationResult::NotOwner)?;
+            return Ok(());
+        }
+    };
+    let intent = EnergyTransferIntent {
+        structure: aux.entity_id,
+        target,
+    };
+    let res = check_energy_transfer_intent(&intent, user_id, FromWorld::from_world(aux.storage()));
+    if let OperationResult::Ok = res {
+        vm.get_aux_mut().intents.energy_transfer_intent = Some(intent);
+    }
+    vm.stack_push(res)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{
+        CarryComponent, EnergyComponent, OwnedEntity, PositionComponent, Structure,
+    };
+    use crate::indices::UserId;
+    use crate::prelude::World;
+    use crate::query;
+    use crate::systems::script_execution::get_alloc;
+
+    #[test]
+    fn test_transfer_energy_requires_range() {
+        let mut storage = World::new();
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let structure = storage.insert_entity();
+        let near = storage.insert_entity();
+        let far = storage.insert_entity();
+        let pos = |q, r| {
+            PositionComponent(WorldPosition {
+                room: Axial::new(0, 0),
+                pos: Axial::new(q, r),
+            })
+        };
+        let carry = CarryComponent::new(50);
+        query!(
+            mutate
+            storage
+            {
+                EntityId, Structure, .insert(structure);
+                EntityId, OwnedEntity, .insert(structure, OwnedEntity { owner_id: user_id });
+                EntityId, EnergyComponent, .insert(
+                    structure,
+                    EnergyComponent {
+                        energy: 100,
+                        energy_max: 500,
+                    }
+                );
+                EntityId, PositionComponent, .insert(structure, pos(5, 5));
+                EntityId, PositionComponent, .insert(near, pos(5, 6));
+                EntityId, PositionComponent, .insert(far, pos(5, 8));
+                EntityId, CarryComponent, .insert(near, carry);
+                EntityId, CarryComponent, .insert(far, carry);
+            }
+        );
+
+        let data = ScriptExecutionData::new(
+            &storage,
+            Default::default(),
+            structure,
+            Some(user_id),
+            get_alloc(),
+        );
+        let mut vm = Vm::new(data).unwrap();
+
+        transfer_energy(&mut vm, far).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::NotInRange);
+        assert!(vm.get_aux().intents.energy_transfer_intent.is_none());
+
+        transfer_energy(&mut vm, near).unwrap();
+        let res: OperationResult = vm.stack_pop().try_into().unwrap();
+        assert_eq!(res, OperationResult::Ok);
+        let intent = vm.get_aux().intents.energy_transfer_intent.clone().unwrap();
+        assert_eq!(intent.target, near);
+    }
+}
@@ -159,3 +159,110 @@ lanes:
     vm.register_function("say", into_f1(say));
     vm.run(&program).unwrap_err();
 }
+
+#[test]
+fn test_cpu_remaining() {
+    let storage = init_basic_storage();
+    let mut data = ScriptExecutionData::new(
+        &storage,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        get_alloc(),
+    );
+    data.cpu_remaining = Some(42);
+    let mut vm = Vm::new(data).unwrap();
+
+    fn assert_cpu(_vm: &mut Vm<ScriptExecutionData>, cpu: i64) -> Result<(), ExecutionError> {
+        assert_eq!(cpu, 42);
+        Ok(())
+    }
+
+    vm.register_function("cpu_remaining", cpu_remaining);
+    vm.register_function("assert_cpu", into_f1(assert_cpu));
+
+    const PROGRAM: &str = r#"
+lanes:
+    - cards:
+        - ty: CallNative
+          val: "cpu_remaining"
+        - ty: CallNative
+          val: "assert_cpu"
+    "#;
+
+    let program = serde_yaml::from_str(PROGRAM).unwrap();
+    let program = compile(&program, None).unwrap();
+
+    vm.run(&program).unwrap();
+}
+
+#[test]
+fn test_init_string() {
+    let storage = init_basic_storage();
+    let data = ScriptExecutionData::new(
+        &storage,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        get_alloc(),
+    );
+    let mut vm = Vm::new(data).unwrap();
+
+    for len in [0, 12, 13, 60, 61, MAX_STRING_LEN] {
+        let value = "a".repeat(len);
+        let ptr = init_string(&mut vm, value.as_str()).unwrap();
+        assert_eq!(unsafe { ptr.get_str() }, Some(value.as_str()));
+    }
+    assert!(init_string(&mut vm, "a".repeat(MAX_STRING_LEN + 1).as_str()).is_err());
+}
+
+#[test]
+fn test_action_and_query_results() {
+    let storage = init_basic_storage();
+    let mut vm = Vm::new(ScriptExecutionData::new(
+        &storage,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        get_alloc(),
+    ))
+    .unwrap();
+
+    fn not_in_range(vm: &mut Vm<ScriptExecutionData>) -> Result<(), ExecutionError> {
+        vm.stack_push(OperationResult::NotInRange)?;
+        Ok(())
+    }
+    let get = |value: Value, key: &str| match value {
+        Value::Object(t) => unsafe { (*t).get_value(Handle::from_str(key).unwrap()) },
+        value => panic!("Expected an object, got {:?}", value),
+    };
+
+    ImportKind::Action.wrap(not_in_range).call(&mut vm).unwrap();
+    let res = vm.stack_pop();
+    assert!(matches!(get(res, "ok"), Some(Value::Integer(0))));
+    assert!(matches!(get(res, "code"), Some(Value::Integer(4))));
+    let reason = match get(res, "reason") {
+        Some(Value::String(s)) => unsafe { s.get_str() }.unwrap().to_owned(),
+        value => panic!("Expected a string, got {:?}", value),
+    };
+    assert_eq!(reason, OperationResult::NotInRange.reason());
+
+    // the entity has no owner, so there is no cpu budget
+    ImportKind::Query.wrap(cpu_remaining).call(&mut vm).unwrap();
+    let res = vm.stack_pop();
+    assert!(matches!(get(res, "found"), Some(Value::Integer(0))));
+    assert!(matches!(get(res, "value"), Some(Value::Nil)));
+}
+
+#[test]
+fn test_docs_list_every_import() {
+    let schema = make_import();
+    let docs = schema.docs();
+    for name in schema.keys() {
+        assert!(
+            docs.contains(&format!("## {}\n", name)),
+            "{} is not documented",
+            name
+        );
+    }
+}
@@ -23,6 +23,12 @@
 /// let entity_1 = store.insert_entity();
 /// let entity_2 = store.insert_entity();
 ///
+/// let carry = |amount| {
+///     let mut carry = CarryComponent::new(69);
+///     carry.add(Resource::Energy, amount);
+///     carry
+/// };
+///
 /// query!(
 ///     mutate
 ///     store
@@ -30,9 +36,9 @@
 ///         EntityId, Bot, .insert(entity_1);
 ///         EntityId, Bot, .insert(entity_2);
 ///         EntityId, CarryComponent,
-///                  .insert(entity_1, CarryComponent{carry: 12, carry_max: 69});
+///                  .insert(entity_1, carry(12));
 ///         EntityId, CarryComponent,
-///                  .insert(entity_2, CarryComponent{carry: 0, carry_max: 69});
+///                  .insert(entity_2, carry(0));
 ///     }
 /// );
 /// ```
@@ -82,6 +88,12 @@ macro_rules! query {
 ///
 /// // Initialize entities ...
 ///
+/// let carry = |amount| {
+///     let mut carry = CarryComponent::new(69);
+///     carry.add(Resource::Energy, amount);
+///     carry
+/// };
+///
 /// query!(
 ///    mutate
 ///    store
@@ -96,11 +108,11 @@ macro_rules! query {
 ///        // notice how entity_3 is not a bot, but has carry
 ///
 ///        EntityId, CarryComponent,
-///                 .insert(entity_1, CarryComponent{carry: 12, carry_max: 69});
+///                 .insert(entity_1, carry(12));
 ///        EntityId, CarryComponent,
-///                 .insert(entity_2, CarryComponent{carry: 30, carry_max: 69});
+///                 .insert(entity_2, carry(30));
 ///        EntityId, CarryComponent,
-///                 .insert(entity_3, CarryComponent{carry: 40, carry_max: 69});
+///                 .insert(entity_3, carry(40));
 ///    }
 /// );
 ///
@@ -117,7 +129,7 @@ macro_rules! query {
 ///     // we'll extract the carry amount
 ///     //
 ///     // pos_components are default (0,0), we access them for demo purposes...
-///     .map(|(id, (bot, pos, car))|{ car.carry as i32 + pos.0.pos.q })
+///     .map(|(id, (bot, pos, car))|{ car.carry() as i32 + pos.0.pos.q })
 ///     .sum();
 ///
 /// assert_eq!(res, 42); // entity_1 carry + entity_2 carry
@@ -144,6 +156,12 @@ macro_rules! query {
 /// let entity_2 = store.insert_entity();
 /// let entity_3 = store.insert_entity();
 ///
+/// let carry = |amount| {
+///     let mut carry = CarryComponent::new(69);
+///     carry.add(Resource::Energy, amount);
+///     carry
+/// };
+///
 /// query!(
 ///     mutate
 ///     store
@@ -158,11 +176,11 @@ macro_rules! query {
 ///         // notice how entity_3 is not a bot, but has carry
 ///
 ///         EntityId, CarryComponent,
-///                  .insert(entity_1, CarryComponent{carry: 12, carry_max: 69});
+///                  .insert(entity_1, carry(12));
 ///         EntityId, CarryComponent,
-///                  .insert(entity_2, CarryComponent{carry: 30, carry_max: 69});
+///                  .insert(entity_2, carry(30));
 ///         EntityId, CarryComponent,
-///                  .insert(entity_3, CarryComponent{carry: 40, carry_max: 69});
+///                  .insert(entity_3, carry(40));
 ///     }
 /// );
 ///
@@ -179,7 +197,7 @@ macro_rules! query {
 ///     //
 ///     // pos_components are default (0,0), we access them for demo purposes...
 ///     .map(|(id, (_bot_component, pos_component, carry_component))| {
-///         carry_component.carry as i32 + pos_component.0.pos.q
+///         carry_component.carry() as i32 + pos_component.0.pos.q
 ///     })
 ///     .sum();
 ///
@@ -312,7 +330,7 @@ macro_rules! archetype {
             use crate::storage::{HasTable, DeleteById, DeferredDeleteById};
             pub type Key = $id;
 
-            #[derive(cao_storage_derive::CaoStorage, Default)]
+            #[derive(cao_storage_derive::CaoStorage, Default, serde::Serialize, serde::Deserialize)]
             $(
                 #[cao_storage_table($id, $name, $row)]
             )*
@@ -339,6 +357,18 @@ macro_rules! archetype {
                         $( $name ),*
                     }
                 }
+
+                /// Hash of each table in this archetype, in declaration order
+                pub fn table_hashes(&self) -> Vec<(&'static str, u64)> {
+                    ::std::vec![
+                        $(
+                            (
+                                ::std::concat!(::std::stringify!($module), "::", ::std::stringify!($row)),
+                                crate::world::state_hash::hash_table(&self.$name)
+                            )
+                        ),*
+                    ]
+                }
             }
         }
     };
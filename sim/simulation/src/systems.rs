@@ -1,85 +1,136 @@
 pub mod attack_system;
+pub mod build_intent_system;
+pub mod construction_system;
+pub mod cpu_budget_system;
 pub mod death_system;
 pub mod decay_system;
 pub mod dropoff_intent_system;
 pub mod energy_system;
+pub mod energy_transfer_intent_system;
+pub mod heal_intent_system;
 pub mod log_intent_system;
 pub mod log_system;
+pub mod message_system;
 pub mod mine_intent_system;
 pub mod mineral_system;
 pub mod move_intent_system;
 pub mod path_cache_intent_system;
 pub mod positions_system;
+pub mod repair_intent_system;
 pub mod say_intent_system;
+pub mod script_error_system;
 pub mod script_execution;
 pub mod script_history_system;
+pub mod script_memory_system;
 pub mod spawn_system;
+pub mod withdraw_intent_system;
 
 use attack_system::attack_system_update;
+use build_intent_system::build_intents_update;
+use construction_system::construction_update;
+use cpu_budget_system::cpu_budget_update;
 use death_system::death_update;
 use decay_system::decay_update;
 use dropoff_intent_system::dropoff_intents_update;
 use energy_system::energy_update;
+use energy_transfer_intent_system::energy_transfer_intents_update;
+use heal_intent_system::heal_intents_update;
 use log_intent_system::log_intents_update;
 use log_system::log_update;
+use message_system::messages_update;
 use mine_intent_system::mine_intents_update;
 use mineral_system::mineral_update;
 use move_intent_system::move_intents_update;
 use path_cache_intent_system::path_cache_intents_update;
 use positions_system::positions_update;
+use repair_intent_system::repair_intents_update;
 use say_intent_system::say_intents_update;
+use script_error_system::script_errors_update;
 use script_history_system::script_history_update;
-use spawn_system::{update_spawn_intents, update_spawns};
+use script_memory_system::script_memory_update;
+use spawn_system::{update_spawn_intents, update_spawn_script_intents, update_spawns};
+use withdraw_intent_system::withdraw_intents_update;
+
+use std::time::{Duration, Instant};
 
 use crate::storage::views::{FromWorld, FromWorldMut};
 use crate::{prelude::World, profile};
 
-pub fn execute_world_update(storage: &mut World) {
+/// Duration of each system in a world update, in execution order
+pub type SystemDurations = Vec<(&'static str, Duration)>;
+
+pub fn execute_world_update(storage: &mut World) -> SystemDurations {
     profile!("execute_systems_update");
 
-    execute_intents(storage);
-    execute_automated_systems(storage);
+    let mut durations = Vec::with_capacity(32);
+    execute_intents(storage, &mut durations);
+    execute_automated_systems(storage, &mut durations);
+    durations
 }
 
-fn execute_intents(storage: &mut World) {
+fn execute_intents(storage: &mut World, durations: &mut SystemDurations) {
     profile!("execute_intents");
 
     // pre processing
-    execute_update(spawn_system::update_cont_spawns, storage);
+    execute_update(
+        spawn_system::update_continuous_spawn_intents,
+        storage,
+        durations,
+    );
+    execute_update(spawn_system::update_cont_spawns, storage, durations);
 
     // main processing
-    execute_update(attack_system_update, storage);
-    execute_update(move_intents_update, storage);
-    execute_update(mine_intents_update, storage);
-    execute_update(dropoff_intents_update, storage);
-    execute_update(update_spawn_intents, storage);
-    execute_update(log_intents_update, storage);
-    execute_update(path_cache_intents_update, storage);
-    execute_update(script_history_update, storage);
-    execute_update(say_intents_update, storage);
+    execute_update(attack_system_update, storage, durations);
+    execute_update(heal_intents_update, storage, durations);
+    execute_update(repair_intents_update, storage, durations);
+    execute_update(move_intents_update, storage, durations);
+    execute_update(mine_intents_update, storage, durations);
+    execute_update(dropoff_intents_update, storage, durations);
+    execute_update(withdraw_intents_update, storage, durations);
+    execute_update(energy_transfer_intents_update, storage, durations);
+    execute_update(build_intents_update, storage, durations);
+    execute_update(construction_update, storage, durations);
+    execute_update(update_spawn_intents, storage, durations);
+    execute_update(update_spawn_script_intents, storage, durations);
+    execute_update(log_intents_update, storage, durations);
+    execute_update(path_cache_intents_update, storage, durations);
+    execute_update(script_history_update, storage, durations);
+    execute_update(script_errors_update, storage, durations);
+    execute_update(script_memory_update, storage, durations);
+    execute_update(messages_update, storage, durations);
+    execute_update(cpu_budget_update, storage, durations);
+    execute_update(say_intents_update, storage, durations);
 }
 
 /// Execute systems that run regardless of player actions
-fn execute_automated_systems(storage: &mut World) {
+fn execute_automated_systems(storage: &mut World, durations: &mut SystemDurations) {
     profile!("execute_automated_systems");
 
-    execute_update(decay_update, storage);
-    execute_update(death_update, storage);
-    execute_update(energy_update, storage);
-    execute_update(update_spawns, storage);
-    execute_update(mineral_update, storage);
-    execute_update(positions_update, storage);
-    execute_update(log_update, storage);
+    execute_update(decay_update, storage, durations);
+    execute_update(death_update, storage, durations);
+    execute_update(energy_update, storage, durations);
+    execute_update(update_spawns, storage, durations);
+    execute_update(mineral_update, storage, durations);
+    execute_update(positions_update, storage, durations);
+    execute_update(log_update, storage, durations);
 }
 
 #[inline]
-fn execute_update<'a, M, C, Sys>(sys: Sys, storage: &'a mut World)
+fn execute_update<'a, M, C, Sys>(sys: Sys, storage: &'a mut World, durations: &mut SystemDurations)
 where
     Sys: Fn(M, C) + 'a,
     M: FromWorldMut + Clone + 'a,
     C: FromWorld<'a> + 'a,
 {
+    let start = Instant::now();
     let m = M::from_world_mut(storage);
     let c = C::from_world(storage as &_);
     sys(M::clone(&m), c);
+    durations.push((system_name::<Sys>(), start.elapsed()));
+}
+
+/// Name of the system function, without its module path
+fn system_name<Sys>() -> &'static str {
+    let name = std::any::type_name::<Sys>();
+    name.rsplit("::").next().unwrap_or(name)
 }
@@ -1,55 +1,186 @@
-use crate::components::{HpComponent, MeleeAttackComponent};
+use crate::components::{
+    ArmorComponent, HpComponent, MeleeAttackComponent, PositionComponent, RangedAttackComponent,
+};
 use crate::indices::*;
 use crate::intents::*;
 use crate::profile;
 use crate::storage::views::{UnsafeView, UnwrapViewMut, View};
+use std::collections::HashMap;
 use tracing::{debug, error};
 
 type Mut = (
     UnsafeView<EntityId, HpComponent>,
     UnwrapViewMut<EmptyKey, Intents<MeleeIntent>>,
+    UnwrapViewMut<EmptyKey, Intents<RangedIntent>>,
+);
+type Const<'a> = (
+    View<'a, EntityId, MeleeAttackComponent>,
+    View<'a, EntityId, RangedAttackComponent>,
+    View<'a, EntityId, ArmorComponent>,
+    View<'a, EntityId, PositionComponent>,
 );
-type Const<'a> = (View<'a, EntityId, MeleeAttackComponent>,);
 
-pub fn attack_system_update((mut hp_table, mut intents): Mut, (attack_table,): Const) {
+/// Attacks are resolved simultaneously: the damage of every attack is summed per defender
+/// before any hp is taken, so entities killed in this tick still land their own attacks and
+/// the outcome does not depend on the order of the intents.
+///
+/// The armor of the defender is subtracted from every single hit.
+pub fn attack_system_update(
+    (mut hp_table, mut melee_intents, mut ranged_intents): Mut,
+    (melee_table, ranged_table, armor_table, pos_table): Const,
+) {
     profile!("AttackSystem update");
 
-    pre_process(&mut intents.0);
+    pre_process(&mut melee_intents.0, |intent| intent.attacker);
+    pre_process(&mut ranged_intents.0, |intent| intent.attacker);
+
+    let mut damage = HashMap::<EntityId, u16>::new();
+    let mut hit = |defender: EntityId, strength: u16| {
+        let armor = armor_table.get(defender).map(|a| a.armor).unwrap_or(0);
+        let d = damage.entry(defender).or_insert(0);
+        *d = d.saturating_add(strength.saturating_sub(armor));
+    };
 
-    for intent in intents.iter() {
-        let attack = match attack_table.get(intent.attacker) {
+    for intent in melee_intents.iter() {
+        match melee_table.get(intent.attacker) {
+            Some(attack) => hit(intent.defender, attack.strength),
+            None => error!("Attacker has no attack component. {:?}", intent),
+        }
+    }
+    for intent in ranged_intents.iter() {
+        let attack = match ranged_table.get(intent.attacker) {
             Some(s) => s,
             None => {
-                error!("Attacker has no attack component. {:?}", intent);
+                error!("Attacker has no ranged attack component. {:?}", intent);
                 continue;
             }
         };
-        let hp = match hp_table.get_mut(intent.defender) {
-            Some(s) => s,
-            None => {
-                error!("Defender has no hp component. {:?}", intent);
+        let distance = match (
+            pos_table.get(intent.attacker),
+            pos_table.get(intent.defender),
+        ) {
+            (Some(a), Some(d)) if a.0.room == d.0.room => a.0.pos.hex_distance(d.0.pos),
+            _ => {
+                debug!(
+                    "Attacker and defender are not in the same room. {:?}",
+                    intent
+                );
                 continue;
             }
         };
-        // hp can not fall below 0
-        hp.hp -= hp.hp.min(attack.strength);
+        hit(intent.defender, attack.damage_at(distance));
+    }
+
+    for (defender, damage) in damage {
+        match hp_table.get_mut(defender) {
+            // hp can not fall below 0
+            Some(hp) => hp.hp -= hp.hp.min(damage),
+            None => error!("Defender {:?} has no hp component", defender),
+        }
     }
 }
 
-fn pre_process(intents: &mut Vec<MeleeIntent>) {
-    let len = intents.len();
-    if len < 2 {
+/// Every attacker may attack once per tick
+fn pre_process<T: std::fmt::Debug>(intents: &mut Vec<T>, attacker: impl Fn(&T) -> EntityId) {
+    if intents.len() < 2 {
         return;
     }
     // dedupe
-    intents.sort_unstable_by_key(|intent| intent.attacker);
-    for current in (0..len).rev() {
-        let last = current + 1;
-        let a = &intents[last];
-        let b = &intents[current];
-        if a.attacker == b.attacker {
+    intents.sort_by_key(|intent| attacker(intent));
+    intents.dedup_by(|a, b| {
+        let duplicate = attacker(a) == attacker(b);
+        if duplicate {
             debug!("Duplicated attacker, removing {:?}", a);
-            intents.swap_remove(last);
         }
+        duplicate
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_attacks_are_resolved_simultaneously() {
+        let mut world = World::new();
+        let a = world.insert_entity();
+        let b = world.insert_entity();
+        let archer = world.insert_entity();
+        let pos = |q| {
+            PositionComponent(WorldPosition {
+                room: Axial::new(0, 0),
+                pos: Axial::new(q, 0),
+            })
+        };
+        let hp = HpComponent { hp: 10, hp_max: 10 };
+        query!(
+            mutate
+            world
+            {
+                EntityId, HpComponent, .insert(a, hp);
+                EntityId, HpComponent, .insert(b, HpComponent { hp: 20, hp_max: 20 });
+                EntityId, HpComponent, .insert(archer, hp);
+                EntityId, PositionComponent, .insert(a, pos(0));
+                EntityId, PositionComponent, .insert(b, pos(1));
+                EntityId, PositionComponent, .insert(archer, pos(4));
+                EntityId, MeleeAttackComponent, .insert(a, MeleeAttackComponent { strength: 20 });
+                EntityId, MeleeAttackComponent, .insert(b, MeleeAttackComponent { strength: 20 });
+                EntityId, ArmorComponent, .insert(b, ArmorComponent { armor: 4 });
+                EntityId, RangedAttackComponent, .insert(
+                    archer,
+                    RangedAttackComponent { strength: 9, range: 3 }
+                );
+            }
+        );
+        *UnwrapViewMut::<EmptyKey, Intents<MeleeIntent>>::from_world_mut(&mut world) =
+            Intents(vec![
+                MeleeIntent {
+                    attacker: a,
+                    defender: b,
+                },
+                MeleeIntent {
+                    attacker: b,
+                    defender: a,
+                },
+            ]);
+        *UnwrapViewMut::<EmptyKey, Intents<RangedIntent>>::from_world_mut(&mut world) =
+            Intents(vec![RangedIntent {
+                attacker: archer,
+                defender: b,
+            }]);
+
+        attack_system_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+
+        let hp = world.view::<EntityId, HpComponent>();
+        // `a` dies but still lands its attack
+        assert_eq!(hp.get(a).unwrap().hp, 0);
+        // 20 - 4 melee damage and the 3 damage at the edge of the range is absorbed by the armor
+        assert_eq!(hp.get(b).unwrap().hp, 4);
+        assert_eq!(hp.get(archer).unwrap().hp, 10);
+    }
+
+    #[test]
+    fn test_ranged_damage_falls_off() {
+        let attack = RangedAttackComponent {
+            strength: 9,
+            range: 3,
+        };
+        assert_eq!(attack.damage_at(1), 9);
+        assert_eq!(attack.damage_at(2), 6);
+        assert_eq!(attack.damage_at(3), 3);
+        assert_eq!(attack.damage_at(4), 0);
+
+        let attack = RangedAttackComponent {
+            strength: 9,
+            range: 0,
+        };
+        assert_eq!(attack.damage_at(0), 0);
+        assert_eq!(attack.damage_at(1), 0);
     }
 }
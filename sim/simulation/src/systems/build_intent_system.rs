@@ -0,0 +1,78 @@
+use crate::components::game_config::GameConfig;
+use crate::components::{
+    ConstructionSiteComponent, EnergyComponent, EntityComponent, OwnedEntity, PositionComponent,
+    Structure,
+};
+use crate::indices::*;
+use crate::intents::{BuildIntent, Intents};
+use crate::profile;
+use crate::storage::views::{InsertEntityView, UnsafeView, UnwrapView, UnwrapViewMut, View};
+use std::collections::HashSet;
+use std::mem::take;
+use tracing::{debug, trace};
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<BuildIntent>>,
+    UnsafeView<EntityId, Structure>,
+    UnsafeView<EntityId, ConstructionSiteComponent>,
+    UnsafeView<EntityId, EnergyComponent>,
+    UnsafeView<EntityId, OwnedEntity>,
+    UnsafeView<EntityId, PositionComponent>,
+    InsertEntityView,
+);
+type Const<'a> = (
+    View<'a, WorldPosition, EntityComponent>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+/// Place construction sites. If multiple sites are placed on the same position, the first
+/// intent wins
+pub fn build_intents_update(
+    (
+        mut intents,
+        mut structures,
+        mut sites,
+        mut energy,
+        mut owners,
+        mut positions,
+        mut insert_entity,
+    ): Mut,
+    (entities, config): Const,
+) {
+    profile!("BuildSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    let mut taken = HashSet::with_capacity(intents.len());
+    for intent in intents {
+        trace!("Executing build intent {:?}", intent);
+        let cost = match config.construction_cost.cost(intent.ty) {
+            Some(cost) => cost,
+            None => {
+                debug!("{:?} can not be built", intent.ty);
+                continue;
+            }
+        };
+        if entities.get(intent.pos).is_some() || !taken.insert(intent.pos) {
+            debug!("Position {:?} is taken", intent.pos);
+            continue;
+        }
+
+        let id = unsafe { insert_entity.insert_entity() };
+        structures.insert(id);
+        sites.insert(id, ConstructionSiteComponent(intent.ty));
+        energy.insert(
+            id,
+            EnergyComponent {
+                energy: 0,
+                energy_max: cost,
+            },
+        );
+        owners.insert(
+            id,
+            OwnedEntity {
+                owner_id: intent.owner_id,
+            },
+        );
+        positions.insert(id, PositionComponent(intent.pos));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::components::{
+    ConstructionSiteComponent, EnergyComponent, HpComponent, InventoryComponent,
+    RangedAttackComponent, Structure, StructureType,
+};
+use crate::entity_archetypes::{init_structure_container, init_structure_tower};
+use crate::indices::*;
+use crate::profile;
+use crate::storage::views::UnsafeView;
+use crate::tables::Table;
+use tracing::{trace, warn};
+
+type Mut = (
+    UnsafeView<EntityId, ConstructionSiteComponent>,
+    UnsafeView<EntityId, Structure>,
+    UnsafeView<EntityId, HpComponent>,
+    UnsafeView<EntityId, EnergyComponent>,
+    UnsafeView<EntityId, InventoryComponent>,
+    UnsafeView<EntityId, RangedAttackComponent>,
+);
+
+/// Turn the construction sites that received all their energy into structures
+pub fn construction_update(
+    (mut sites, structures, hps, mut energy, inventory, ranged): Mut,
+    (): (),
+) {
+    profile!("ConstructionSystem update");
+
+    let finished = sites
+        .iter()
+        .filter(|(id, _)| {
+            energy
+                .get(*id)
+                .map(|e| e.energy >= e.energy_max)
+                .unwrap_or(false)
+        })
+        .map(|(id, ConstructionSiteComponent(ty))| (id, *ty))
+        .collect::<Vec<_>>();
+
+    for (id, ty) in finished {
+        trace!("Construction of {:?} {:?} is done", ty, id);
+        sites.delete(id);
+        energy.delete(id);
+        let tables = (structures, hps, inventory, ranged);
+        match ty {
+            StructureType::Container => init_structure_container(id, tables),
+            StructureType::Tower => init_structure_tower(id, tables),
+            StructureType::Spawn => warn!("Spawns can not be constructed {:?}", id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::FromWorldMut;
+
+    #[test]
+    fn test_full_sites_become_structures() {
+        let mut world = World::new();
+        let done = world.insert_entity();
+        let pending = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, Structure, .insert(done);
+                EntityId, ConstructionSiteComponent, .insert(done, ConstructionSiteComponent(StructureType::Tower));
+                EntityId, EnergyComponent, .insert(done, EnergyComponent { energy: 1000, energy_max: 1000 });
+                EntityId, Structure, .insert(pending);
+                EntityId, ConstructionSiteComponent, .insert(pending, ConstructionSiteComponent(StructureType::Container));
+                EntityId, EnergyComponent, .insert(pending, EnergyComponent { energy: 499, energy_max: 500 });
+            }
+        );
+
+        construction_update(FromWorldMut::from_world_mut(&mut world), ());
+
+        let sites = world.view::<EntityId, ConstructionSiteComponent>();
+        assert!(!sites.contains(done));
+        assert!(sites.contains(pending));
+        assert!(world
+            .view::<EntityId, EnergyComponent>()
+            .get(done)
+            .is_none());
+        assert!(world
+            .view::<EntityId, RangedAttackComponent>()
+            .contains(done));
+        assert!(world.view::<EntityId, HpComponent>().contains(done));
+        assert!(world.view::<EntityId, Structure>().contains(&done));
+    }
+}
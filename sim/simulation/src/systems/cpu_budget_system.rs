@@ -0,0 +1,124 @@
+use crate::components::{game_config::GameConfig, CpuBudget, UserComponent, UserProperties};
+use crate::indices::*;
+use crate::intents::{CpuUsageIntent, Intents};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapView, UnwrapViewMut, View};
+use crate::tables::btree_table::BTreeTable;
+use std::collections::HashMap;
+use std::mem::take;
+use tracing::trace;
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<CpuUsageIntent>>,
+    UnsafeView<UserId, CpuBudget>,
+);
+type Const<'a> = (
+    View<'a, UserId, UserComponent>,
+    View<'a, UserId, UserProperties>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+/// Charge the cpu used in this tick and refill the budgets of every user
+pub fn cpu_budget_update((mut intents, mut budgets): Mut, (users, user_props, config): Const) {
+    profile!("CpuBudgetSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    // a user may be charged multiple times in a tick, e.g. for path finding
+    let mut usage = HashMap::<UserId, u64>::new();
+    for intent in intents {
+        *usage.entry(intent.user_id).or_default() += intent.instructions;
+    }
+
+    for (user_id, _) in users.iter() {
+        let level = user_props
+            .get(user_id)
+            .map(|props| props.level)
+            .unwrap_or_else(|| UserProperties::default().level);
+        let available = available_cpu(&budgets, &config, user_id, level);
+        let used = usage.get(&user_id).copied().unwrap_or(0);
+        let available = (available.saturating_sub(used) + config.cpu_per_tick(level))
+            .min(config.cpu_bucket_limit(level));
+        trace!(
+            "User {:?} used {} instructions, {} available",
+            user_id,
+            used,
+            available
+        );
+        budgets.insert(user_id, CpuBudget { available });
+    }
+}
+
+/// Cpu available to the user in the current tick
+///
+/// Users that have not been charged yet start with a single tick's worth of budget
+pub fn available_cpu(
+    budgets: &BTreeTable<UserId, CpuBudget>,
+    config: &GameConfig,
+    user_id: UserId,
+    level: u16,
+) -> u64 {
+    budgets
+        .get(user_id)
+        .map(|budget| budget.available)
+        .unwrap_or_else(|| config.cpu_per_tick(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_unused_budget_accumulates_up_to_the_limit() {
+        let mut world = World::new();
+        {
+            let mut config = world.unsafe_view::<ConfigKey, GameConfig>();
+            let config = config.unwrap_mut();
+            config.cpu_per_level = 100;
+            config.cpu_bucket_ticks = 3;
+        }
+
+        let user_id = UserId(uuid::Uuid::new_v4());
+        query!(
+            mutate
+            world
+            {
+                UserId, UserComponent, .insert(user_id);
+                UserId, UserProperties, .insert(user_id, UserProperties { level: 2 });
+            }
+        );
+
+        let mut tick = |instructions: &[u64]| {
+            *UnwrapViewMut::<EmptyKey, Intents<CpuUsageIntent>>::from_world_mut(&mut world) =
+                Intents(
+                    instructions
+                        .iter()
+                        .map(|instructions| CpuUsageIntent {
+                            user_id,
+                            instructions: *instructions,
+                        })
+                        .collect(),
+                );
+            cpu_budget_update(
+                FromWorldMut::from_world_mut(&mut world),
+                FromWorld::from_world(&world),
+            );
+            world
+                .view::<UserId, CpuBudget>()
+                .get(user_id)
+                .unwrap()
+                .available
+        };
+
+        // starts with a single tick's worth
+        assert_eq!(tick(&[150]), 200 - 150 + 200);
+        assert_eq!(tick(&[0]), 250 + 200);
+        // capped at 3 ticks worth
+        assert_eq!(tick(&[0]), 600);
+        assert_eq!(tick(&[600]), 200);
+        // multiple charges in the same tick add up
+        assert_eq!(tick(&[100, 50]), 200 - 150 + 200);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::components::{CarryComponent, DropoffEventComponent, EnergyComponent};
+use crate::components::{
+    CarryComponent, DropoffEventComponent, EnergyComponent, InventoryComponent,
+};
 use crate::indices::*;
 use crate::intents::*;
 use crate::profile;
@@ -7,13 +9,14 @@ use tracing::{trace, warn};
 
 type Mut = (
     UnsafeView<EntityId, EnergyComponent>,
+    UnsafeView<EntityId, InventoryComponent>,
     UnsafeView<EntityId, CarryComponent>,
     UnsafeView<EntityId, DropoffEventComponent>,
 );
 type Const<'a> = (UnwrapView<'a, EmptyKey, Intents<DropoffIntent>>,);
 
 pub fn dropoff_intents_update(
-    (mut energy_table, mut carry_table, mut events): Mut,
+    (mut energy_table, mut inventory_table, mut carry_table, mut events): Mut,
     (intents,): Const,
 ) {
     profile!("DropoffSystem update");
@@ -29,20 +32,25 @@ pub fn dropoff_intents_update(
                 continue;
             }
         };
-        let store_component = match energy_table.get_mut(intent.structure) {
-            Some(x) => x,
-            None => {
-                warn!("Structure has no energy");
-                continue;
-            }
-        };
-        let dropoff = intent
-            .amount
-            .min(carry_component.carry)
-            .min(store_component.energy_max - store_component.energy);
+        if let Some(inventory) = inventory_table.get_mut(intent.structure) {
+            let dropoff = intent.amount.min(inventory.free());
+            let dropoff = carry_component.resources.remove(intent.ty, dropoff);
+            inventory.add(intent.ty, dropoff);
+        } else {
+            let store_component = match energy_table.get_mut(intent.structure) {
+                Some(x) => x,
+                None => {
+                    warn!("Structure has no energy");
+                    continue;
+                }
+            };
+            let dropoff = intent
+                .amount
+                .min(store_component.energy_max - store_component.energy);
+            let dropoff = carry_component.resources.remove(intent.ty, dropoff);
 
-        store_component.energy += dropoff;
-        carry_component.carry -= dropoff;
+            store_component.energy += dropoff;
+        }
 
         events.insert(intent.bot, DropoffEventComponent(intent.structure));
     }
@@ -0,0 +1,101 @@
+use crate::components::{CarryComponent, EnergyComponent, Resource};
+use crate::indices::*;
+use crate::intents::{EnergyTransferIntent, Intents};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut};
+use std::mem::take;
+use tracing::{trace, warn};
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<EnergyTransferIntent>>,
+    UnsafeView<EntityId, EnergyComponent>,
+    UnsafeView<EntityId, CarryComponent>,
+);
+
+/// Transfer energy in intent order.
+/// Targets storing energy are filled before their carry is considered.
+pub fn energy_transfer_intents_update(
+    (mut intents, mut energy_table, mut carry_table): Mut,
+    (): (),
+) {
+    profile!("EnergyTransferSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for intent in intents {
+        trace!("Executing energy transfer intent {:?}", intent);
+        let available = match energy_table.get(intent.structure) {
+            Some(e) => e.energy,
+            None => {
+                warn!("Structure has no energy");
+                continue;
+            }
+        };
+        // transfer amount = min(structure energy, target capacity)
+        let transferred = match energy_table.get_mut(intent.target) {
+            Some(store) => {
+                let amount = available.min(store.energy_max.saturating_sub(store.energy));
+                store.energy += amount;
+                amount
+            }
+            None => match carry_table.get_mut(intent.target) {
+                Some(carry) => carry.add(Resource::Energy, available),
+                None => {
+                    warn!("Target can not hold energy");
+                    continue;
+                }
+            },
+        };
+        if let Some(e) = energy_table.get_mut(intent.structure) {
+            e.energy -= transferred;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+
+    #[test]
+    fn test_transfer_is_limited_by_the_capacity_of_the_target() {
+        let mut world = World::new();
+        let structure = world.insert_entity();
+        let bot = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, EnergyComponent, .insert(
+                    structure,
+                    EnergyComponent {
+                        energy: 100,
+                        energy_max: 500,
+                    }
+                );
+                EntityId, CarryComponent, .insert(
+                    bot,
+                    {
+                        let mut carry = CarryComponent::new(150);
+                        carry.add(Resource::Mineral, 80);
+                        carry
+                    }
+                );
+            }
+        );
+        *UnwrapViewMut::<EmptyKey, Intents<EnergyTransferIntent>>::from_world_mut(&mut world) =
+            Intents(vec![EnergyTransferIntent {
+                structure,
+                target: bot,
+            }]);
+
+        energy_transfer_intents_update(FromWorldMut::from_world_mut(&mut world), ());
+
+        let energy = world.view::<EntityId, EnergyComponent>();
+        assert_eq!(energy.get(structure).unwrap().energy, 30);
+        let carry = world.view::<EntityId, CarryComponent>();
+        let carry = carry.get(bot).unwrap();
+        assert_eq!(carry.carry(), 150);
+        assert_eq!(carry.resources.get(Resource::Energy), 70);
+    }
+}
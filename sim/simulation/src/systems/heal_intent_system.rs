@@ -0,0 +1,80 @@
+use crate::components::{HealComponent, HpComponent};
+use crate::indices::*;
+use crate::intents::{HealIntent, Intents};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut, View};
+use std::mem::take;
+use tracing::{trace, warn};
+
+type Mut = (
+    UnsafeView<EntityId, HpComponent>,
+    UnwrapViewMut<EmptyKey, Intents<HealIntent>>,
+);
+type Const<'a> = (View<'a, EntityId, HealComponent>,);
+
+/// Runs after the attacks of the tick. Bots killed in this tick can not be healed
+pub fn heal_intents_update((mut hp_table, mut intents): Mut, (heal_table,): Const) {
+    profile!("HealSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for intent in intents {
+        trace!("Executing heal intent {:?}", intent);
+        let amount = match heal_table.get(intent.healer) {
+            Some(heal) => heal.amount,
+            None => {
+                warn!("Healer has no heal component {:?}", intent);
+                continue;
+            }
+        };
+        match hp_table.get_mut(intent.target) {
+            Some(hp) if hp.hp > 0 => hp.hp = hp.hp.saturating_add(amount).min(hp.hp_max),
+            Some(_) => trace!("Target {:?} is dead", intent.target),
+            None => warn!("Target has no hp component {:?}", intent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_heal_is_capped_and_skips_the_dead() {
+        let mut world = World::new();
+        let healer = world.insert_entity();
+        let wounded = world.insert_entity();
+        let dead = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, HealComponent, .insert(healer, HealComponent { amount: 30 });
+                EntityId, HpComponent, .insert(wounded, HpComponent { hp: 80, hp_max: 100 });
+                EntityId, HpComponent, .insert(dead, HpComponent { hp: 0, hp_max: 100 });
+            }
+        );
+        *UnwrapViewMut::<EmptyKey, Intents<HealIntent>>::from_world_mut(&mut world) =
+            Intents(vec![
+                HealIntent {
+                    healer,
+                    target: wounded,
+                },
+                HealIntent {
+                    healer,
+                    target: dead,
+                },
+            ]);
+
+        heal_intents_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+
+        let hp = world.view::<EntityId, HpComponent>();
+        assert_eq!(hp.get(wounded).unwrap().hp, 100);
+        assert_eq!(hp.get(dead).unwrap().hp, 0);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::components::{game_config::GameConfig, Inbox, OwnedEntity, ScriptMessage, UserChannels};
+use crate::indices::*;
+use crate::intents::{Intents, MessageTarget, MessagesIntent};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapView, UnwrapViewMut, View};
+use std::collections::HashMap;
+use std::mem::take;
+use tracing::trace;
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<MessagesIntent>>,
+    UnsafeView<EntityId, Inbox>,
+    UnsafeView<UserId, UserChannels>,
+);
+type Const<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+/// Replace the messages of the last tick with the ones sent in this tick
+///
+/// Messages are delivered in intent order. Messages to entities of other users and the ones
+/// over the sender's per-tick limit are dropped.
+pub fn messages_update((mut intents, mut inboxes, mut channels): Mut, (owners, config): Const) {
+    profile!("MessageSystem update");
+
+    inboxes.clear();
+    channels.clear();
+
+    let Intents(intents) = take(&mut *intents);
+    // bytes sent by each user in this tick
+    let mut sent = HashMap::new();
+    for MessagesIntent {
+        sender,
+        user_id,
+        messages,
+    } in intents
+    {
+        let sent = sent.entry(user_id).or_insert(0usize);
+        for (target, payload) in messages {
+            if let MessageTarget::Entity(entity) = target {
+                if owners.get(entity).map(|o| o.owner_id) != Some(user_id) {
+                    trace!("{:?} is not owned by {:?}", entity, user_id);
+                    continue;
+                }
+            }
+            let size = target.size() + payload.size();
+            if size > config.message_size_limit || *sent + size > config.user_message_limit {
+                trace!("User {:?} is over the message limit", user_id);
+                continue;
+            }
+            *sent += size;
+            let message = ScriptMessage { sender, payload };
+            match target {
+                MessageTarget::Channel(name) => {
+                    if channels.get(user_id).is_none() {
+                        channels.insert(user_id, UserChannels::default());
+                    }
+                    channels
+                        .get_by_id_mut(user_id)
+                        .unwrap()
+                        .0
+                        .entry(name)
+                        .or_default()
+                        .push(message);
+                }
+                MessageTarget::Entity(target) => match inboxes.get_mut(target) {
+                    Some(inbox) => inbox.0.push(message),
+                    None => {
+                        inboxes.insert(target, Inbox(vec![message]));
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::MemoryValue;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_messages_are_delivered_once() {
+        let mut world = World::new();
+        world
+            .unsafe_view::<ConfigKey, GameConfig>()
+            .unwrap_mut()
+            .user_message_limit = 25;
+
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let sender = world.insert_entity();
+        let receiver = world.insert_entity();
+        let enemy = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, OwnedEntity, .insert(sender, OwnedEntity { owner_id: user_id });
+                EntityId, OwnedEntity, .insert(receiver, OwnedEntity { owner_id: user_id });
+            }
+        );
+
+        let text = |s: &str| MemoryValue::Text(s.to_owned());
+        *UnwrapViewMut::<EmptyKey, Intents<MessagesIntent>>::from_world_mut(&mut world) =
+            Intents(vec![MessagesIntent {
+                sender,
+                user_id,
+                messages: vec![
+                    (MessageTarget::Entity(receiver), text("hello")),
+                    // not owned by the user
+                    (MessageTarget::Entity(enemy), text("hello")),
+                    (MessageTarget::Channel("mine".to_owned()), text("here")),
+                    // over the limit
+                    (MessageTarget::Channel("mine".to_owned()), text("there")),
+                ],
+            }]);
+
+        messages_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+
+        let expected = |s: &str| {
+            vec![ScriptMessage {
+                sender,
+                payload: text(s),
+            }]
+        };
+        {
+            let inboxes = world.view::<EntityId, Inbox>();
+            assert_eq!(inboxes.get(receiver).unwrap().0, expected("hello"));
+            assert!(inboxes.get(enemy).is_none());
+            let channels = world.view::<UserId, UserChannels>();
+            assert_eq!(
+                channels.get(user_id).unwrap().0.get("mine").unwrap(),
+                &expected("here")
+            );
+        }
+
+        // the next tick's update removes the delivered messages
+        messages_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+        assert!(world.view::<EntityId, Inbox>().get(receiver).is_none());
+        assert!(world.view::<UserId, UserChannels>().get(user_id).is_none());
+    }
+}
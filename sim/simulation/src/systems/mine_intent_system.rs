@@ -30,7 +30,7 @@ pub fn mine_intents_update(
     for intent in intents.iter() {
         trace!("Bot {:?} is mining [{:?}]", intent.bot, intent.resource);
         match resource_table.get(intent.resource) {
-            Some(ResourceComponent(Resource::Energy)) => {
+            Some(ResourceComponent(ty)) if *ty != Resource::Empty => {
                 let resource_energy = match energy_table.get_mut(intent.resource) {
                     Some(resource_energy) => {
                         if resource_energy.energy == 0 {
@@ -53,9 +53,8 @@ pub fn mine_intents_update(
                 };
 
                 let mined = resource_energy.energy.min(MINE_AMOUNT); // Max amount that can be mined
-                let mined = (carry.carry_max - carry.carry).min(mined); // Max amount the bot can carry
+                let mined = carry.add(*ty, mined); // Max amount the bot can carry
 
-                carry.carry += mined;
                 resource_energy.energy -= mined;
 
                 event.insert(intent.bot, MineEventComponent(intent.resource));
@@ -66,9 +65,55 @@ pub fn mine_intents_update(
                     resource_energy
                 );
             }
-            Some(ResourceComponent(Resource::Empty)) | None => {
+            Some(_) | None => {
                 warn!("Resource ({:?}) not found", intent.resource)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_resources_share_the_carry_capacity() {
+        let mut world = World::new();
+        let bot = world.insert_entity();
+        let crystal = world.insert_entity();
+        let mineral = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, CarryComponent, .insert(bot, CarryComponent::new(15));
+                EntityId, ResourceComponent, .insert(crystal, ResourceComponent(Resource::Crystal));
+                EntityId, EnergyComponent, .insert(crystal, EnergyComponent { energy: 50, energy_max: 50 });
+                EntityId, ResourceComponent, .insert(mineral, ResourceComponent(Resource::Mineral));
+                EntityId, EnergyComponent, .insert(mineral, EnergyComponent { energy: 200, energy_max: 200 });
+            }
+        );
+
+        for resource in [crystal, mineral].iter().copied() {
+            *UnwrapViewMut::<EmptyKey, Intents<MineIntent>>::from_world_mut(&mut world) =
+                Intents(vec![MineIntent { bot, resource }]);
+            mine_intents_update(
+                FromWorldMut::from_world_mut(&mut world),
+                FromWorld::from_world(&world),
+            );
+        }
+
+        let carry = *world.view::<EntityId, CarryComponent>().get(bot).unwrap();
+        assert_eq!(carry.resources.get(Resource::Crystal), MINE_AMOUNT);
+        assert_eq!(carry.resources.get(Resource::Mineral), 15 - MINE_AMOUNT);
+        assert_eq!(carry.free(), 0);
+        let energy = world.view::<EntityId, EnergyComponent>();
+        assert_eq!(
+            energy.get(mineral).unwrap().energy,
+            200 - (15 - MINE_AMOUNT)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::indices::{EntityId, WorldPosition};
+use crate::indices::{EmptyKey, EntityId, WorldPosition};
 use crate::profile;
 use crate::storage::views::{DeferredDeleteEntityView, UnsafeView, View};
 use crate::tables::JoinIterator;
@@ -7,10 +7,46 @@ use crate::{geometry::Axial, terrain::TileTerrainType};
 use rand::Rng;
 use tracing::{debug, error, trace};
 
+/// How a type of resource deposit is (re)spawned
+#[derive(Debug, Clone, Copy)]
+pub struct DepositRule {
+    /// Amount of the resource in a fresh deposit
+    pub amount: u16,
+    /// Number of ticks a depleted deposit waits before respawning
+    pub respawn_time: i32,
+    /// Maximum distance of the new position from the depleted one
+    pub respawn_range: u16,
+}
+
+/// Rarer resources come in smaller deposits that take longer to respawn, closer to the
+/// depleted deposit
+pub fn deposit_rule(ty: comp::Resource) -> Option<DepositRule> {
+    let rule = match ty {
+        comp::Resource::Empty => return None,
+        comp::Resource::Energy => DepositRule {
+            amount: 100,
+            respawn_time: 2,
+            respawn_range: 30,
+        },
+        comp::Resource::Mineral => DepositRule {
+            amount: 200,
+            respawn_time: 20,
+            respawn_range: 15,
+        },
+        comp::Resource::Crystal => DepositRule {
+            amount: 50,
+            respawn_time: 50,
+            respawn_range: 5,
+        },
+    };
+    Some(rule)
+}
+
 type Mut = (
     UnsafeView<EntityId, comp::PositionComponent>,
     UnsafeView<EntityId, comp::EnergyComponent>,
     UnsafeView<EntityId, comp::RespawnTimer>,
+    UnsafeView<EmptyKey, comp::WorldRng>,
     DeferredDeleteEntityView,
 );
 type Const<'a> = (
@@ -20,17 +56,17 @@ type Const<'a> = (
 );
 
 pub fn mineral_update(
-    (mut entity_positions, mut energy, mut respawn_timer, mut delete_entity_deferred): Mut,
+    (mut entity_positions, mut energy, mut respawn_timer, mut rng, mut delete_entity_deferred): Mut,
     (position_entities, terrain_table, resources): Const,
 ) {
     profile!("Mineral System update");
     debug!("update minerals system called");
 
-    let mut rng = rand::thread_rng();
+    let rng = rng.unwrap_mut();
 
     let minerals_it = resources
         .iter()
-        .filter(|(_, r)| matches!(r.0, comp::Resource::Energy));
+        .filter_map(|(id, r)| deposit_rule(r.0).map(|rule| (id, rule)));
     let entity_positions_it = entity_positions.iter_mut();
     let energy_iter = energy.iter_mut();
     let respawn_timer = respawn_timer.iter_mut();
@@ -38,11 +74,11 @@ pub fn mineral_update(
     // in case of an error we need to clean up the mineral
     // however best not to clean it inside the iterator, hmmm???
     join!([minerals_it, entity_positions_it, energy_iter, respawn_timer]).for_each(
-        |(id, (_resource, position, energy, respawn))| {
+        |(id, (rule, position, energy, respawn))| {
             trace!(
                 "updating {:?} {:?} {:?} {:?} {:?}",
                 id,
-                _resource,
+                rule,
                 position,
                 energy,
                 respawn
@@ -59,7 +95,7 @@ pub fn mineral_update(
 
             trace!("Respawning mineral {:?}", id);
 
-            respawn.0 = 2;
+            respawn.0 = rule.respawn_time;
 
             let position_entities = position_entities
                 .table
@@ -78,9 +114,9 @@ pub fn mineral_update(
             let pos = random_uncontested_pos_in_range(
                 position_entities,
                 terrain_table,
-                &mut rng,
+                rng,
                 position.0.pos,
-                30,
+                rule.respawn_range,
                 2000,
             );
             trace!(
@@ -90,7 +126,8 @@ pub fn mineral_update(
             );
             match pos {
                 Some(pos) => {
-                    energy.energy = energy.energy_max;
+                    energy.energy_max = rule.amount;
+                    energy.energy = rule.amount;
                     position.0.pos = pos;
                 }
                 None => {
@@ -109,7 +146,7 @@ pub fn mineral_update(
 fn random_uncontested_pos_in_range(
     position_entities_table: View<Axial, comp::EntityComponent>,
     terrain_table: View<Axial, comp::TerrainComponent>,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut impl Rng,
     center: Axial,
     range: u16,
     max_tries: u16,
@@ -0,0 +1,79 @@
+use crate::components::{CarryComponent, HpComponent, Resource};
+use crate::indices::*;
+use crate::intents::{Intents, RepairIntent, REPAIR_AMOUNT};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut};
+use std::mem::take;
+use tracing::{trace, warn};
+
+type Mut = (
+    UnsafeView<EntityId, HpComponent>,
+    UnsafeView<EntityId, CarryComponent>,
+    UnwrapViewMut<EmptyKey, Intents<RepairIntent>>,
+);
+
+/// Restore structure hp, paid for with the energy carried by the bots, 1 energy per hp
+pub fn repair_intents_update((mut hp_table, mut carry_table, mut intents): Mut, (): ()) {
+    profile!("RepairSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for intent in intents {
+        trace!("Executing repair intent {:?}", intent);
+        let carry = match carry_table.get_mut(intent.bot) {
+            Some(carry) => carry,
+            None => {
+                warn!("Bot has no carry component {:?}", intent);
+                continue;
+            }
+        };
+        let hp = match hp_table.get_mut(intent.structure) {
+            Some(hp) => hp,
+            None => {
+                warn!("Structure has no hp component {:?}", intent);
+                continue;
+            }
+        };
+        // repair amount = min(carried energy, missing hp, REPAIR_AMOUNT)
+        let missing = hp.hp_max.saturating_sub(hp.hp);
+        hp.hp += carry
+            .resources
+            .remove(Resource::Energy, missing.min(REPAIR_AMOUNT));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::FromWorldMut;
+
+    #[test]
+    fn test_repair_spends_carried_energy() {
+        let mut world = World::new();
+        let bot = world.insert_entity();
+        let structure = world.insert_entity();
+        let mut carry = CarryComponent::new(100);
+        carry.add(Resource::Energy, 5);
+        carry.add(Resource::Mineral, 50);
+        query!(
+            mutate
+            world
+            {
+                EntityId, CarryComponent, .insert(bot, carry);
+                EntityId, HpComponent, .insert(structure, HpComponent { hp: 100, hp_max: 500 });
+            }
+        );
+        *UnwrapViewMut::<EmptyKey, Intents<RepairIntent>>::from_world_mut(&mut world) =
+            Intents(vec![RepairIntent { bot, structure }]);
+
+        repair_intents_update(FromWorldMut::from_world_mut(&mut world), ());
+
+        let hp = world.view::<EntityId, HpComponent>();
+        assert_eq!(hp.get(structure).unwrap().hp, 105);
+        let carry = world.view::<EntityId, CarryComponent>();
+        let carry = carry.get(bot).unwrap();
+        assert_eq!(carry.resources.get(Resource::Energy), 0);
+        assert_eq!(carry.resources.get(Resource::Mineral), 50);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::components::{game_config::GameConfig, OwnedEntity, ScriptError, ScriptErrors};
+use crate::indices::*;
+use crate::intents::Intents;
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapView, UnwrapViewMut, View};
+use std::mem::take;
+use tracing::trace;
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<ScriptError>>,
+    UnsafeView<EntityId, ScriptErrors>,
+    UnsafeView<UserId, ScriptErrors>,
+);
+type Const<'a> = (
+    View<'a, EntityId, OwnedEntity>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
+pub fn script_errors_update(
+    (mut intents, mut entity_errors, mut user_errors): Mut,
+    (owners, config): Const,
+) {
+    profile!("ScriptErrorSystem update");
+
+    let limit = config.script_error_history_len;
+    let Intents(intents) = take(&mut *intents);
+    for error in intents {
+        trace!("Storing script error {:?}", error);
+        if let Some(OwnedEntity { owner_id }) = owners.get(error.entity_id) {
+            match user_errors.get_by_id_mut(*owner_id) {
+                Some(errors) => errors.push(error.clone(), limit),
+                None => {
+                    let mut errors = ScriptErrors::default();
+                    errors.push(error.clone(), limit);
+                    user_errors.insert(*owner_id, errors);
+                }
+            }
+        }
+        let entity_id = error.entity_id;
+        match entity_errors.get_mut(entity_id) {
+            Some(errors) => errors.push(error, limit),
+            None => {
+                let mut errors = ScriptErrors::default();
+                errors.push(error, limit);
+                entity_errors.insert(entity_id, errors);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_keeps_last_errors_per_entity_and_user() {
+        let mut world = World::new();
+        world
+            .unsafe_view::<ConfigKey, GameConfig>()
+            .unwrap_mut()
+            .script_error_history_len = 2;
+
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let entity_id = world.insert_entity();
+        let other_id = world.insert_entity();
+        query!(
+            mutate
+            world
+            {
+                EntityId, OwnedEntity, .insert(entity_id, OwnedEntity { owner_id: user_id });
+            }
+        );
+
+        let errors = (0..3)
+            .map(|time| ScriptError {
+                time,
+                entity_id,
+                ..Default::default()
+            })
+            .chain(std::iter::once(ScriptError {
+                time: 4,
+                entity_id: other_id,
+                ..Default::default()
+            }))
+            .collect();
+        *UnwrapViewMut::<EmptyKey, Intents<ScriptError>>::from_world_mut(&mut world) =
+            Intents(errors);
+
+        script_errors_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+
+        let times = |errors: &ScriptErrors| errors.0.iter().map(|e| e.time).collect::<Vec<_>>();
+
+        let entity_errors = world.view::<EntityId, ScriptErrors>();
+        assert_eq!(times(entity_errors.get(entity_id).unwrap()), vec![1, 2]);
+        assert_eq!(times(entity_errors.get(other_id).unwrap()), vec![4]);
+
+        // `other_id` has no owner, so only the errors of `entity_id` are stored for the user
+        let user_errors = world.view::<UserId, ScriptErrors>();
+        assert_eq!(times(user_errors.get(user_id).unwrap()), vec![1, 2]);
+    }
+}
@@ -1,16 +1,21 @@
 use crate::{
-    components::{game_config::GameConfig, CompiledScriptComponent, EntityScript, OwnedEntity},
+    components::{
+        game_config::GameConfig, CompiledScriptComponent, CpuBudget, EntityScript, MemoryValue,
+        OwnedEntity, ScriptError, ScriptMemory, UserProperties,
+    },
     indices::{ConfigKey, EntityId, ScriptId, UserId},
     intents::*,
     prelude::World,
     profile,
     storage::views::{FromWorld, UnwrapView},
+    systems::cpu_budget_system::available_cpu,
 };
 use cao_alloc::linear::LinearAllocator;
 use cao_lang::prelude::*;
 use rayon::prelude::*;
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     convert::Infallible,
     fmt::{self, Display, Formatter},
     rc::Rc,
@@ -32,38 +37,156 @@ pub enum ExecutionError {
     },
 }
 
+impl ExecutionError {
+    /// Convert into an entry of the script error history
+    pub fn to_script_error(&self, time: u64, entity_id: EntityId) -> ScriptError {
+        let (script_id, error) = match self {
+            ExecutionError::ScriptNotFound(script_id) => (*script_id, self.to_string()),
+            ExecutionError::RuntimeError {
+                script_id, error, ..
+            } => (*script_id, error.to_string()),
+        };
+        ScriptError {
+            time,
+            entity_id,
+            script_id,
+            error,
+        }
+    }
+}
+
+/// Results of a batch of script executions
+#[derive(Debug, Default)]
+pub struct RunResult {
+    pub intents: Vec<BotIntents>,
+    pub num_scripts_ran: u64,
+    pub num_scripts_errored: u64,
+    /// Scripts not executed because their owner ran out of cpu budget
+    pub num_scripts_skipped: u64,
+}
+
+/// A script scheduled for execution, with the cpu reserved for it
+struct ScheduledScript {
+    entity_id: EntityId,
+    script: EntityScript,
+    owner_id: Option<UserId>,
+    max_instr: u64,
+    /// Budget of the owner left after this script's reservation
+    cpu_remaining: Option<u64>,
+    /// Instructions native functions may use in this script
+    native_instr_limit: u64,
+}
+
+/// Reserve the cpu of each script from its owner's budget, in workload order.
+///
+/// Cao-Lang does not report the number of instructions executed, so scripts are charged
+/// the full limit they are given. Scripts of users without budget are skipped.
+///
+/// Native functions, like path finding, run on top of the Vm's instructions. Each script may
+/// use up to its own limit for them, taken from the budget its owner has left after every
+/// script was reserved, so the owner can not overspend. They are charged as used.
+fn schedule_scripts(
+    workload: &[(EntityId, EntityScript)],
+    storage: &World,
+) -> (Vec<ScheduledScript>, Vec<CpuUsageIntent>) {
+    let owners_table = storage.view::<EntityId, OwnedEntity>();
+    let user_props = storage.view::<UserId, UserProperties>();
+    let budgets = storage.view::<UserId, CpuBudget>();
+    let conf = UnwrapView::<ConfigKey, GameConfig>::from_world(storage);
+    let execution_limit = conf.execution_limit as u64;
+
+    // user -> (available, remaining)
+    let mut cpu = BTreeMap::new();
+    let mut scheduled = workload
+        .iter()
+        .filter_map(|(entity_id, script)| {
+            let owner_id = owners_table
+                .get(*entity_id)
+                .map(|OwnedEntity { owner_id }| *owner_id);
+            let (max_instr, cpu_remaining) = match owner_id {
+                Some(owner_id) => {
+                    let (_, remaining) = cpu.entry(owner_id).or_insert_with(|| {
+                        let level = user_props
+                            .get(owner_id)
+                            .map(|props| props.level)
+                            .unwrap_or_else(|| UserProperties::default().level);
+                        let available = available_cpu(&budgets, &conf, owner_id, level);
+                        (available, available)
+                    });
+                    let max_instr = execution_limit.min(*remaining);
+                    *remaining -= max_instr;
+                    (max_instr, Some(*remaining))
+                }
+                None => (execution_limit, None),
+            };
+            if max_instr == 0 {
+                trace!("{:?} is out of cpu, skipping {:?}", owner_id, entity_id);
+                return None;
+            }
+            Some(ScheduledScript {
+                entity_id: *entity_id,
+                script: *script,
+                owner_id,
+                max_instr,
+                cpu_remaining,
+                native_instr_limit: 0,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut unreserved = cpu
+        .iter()
+        .map(|(user_id, (_, remaining))| (*user_id, *remaining))
+        .collect::<BTreeMap<_, _>>();
+    for script in scheduled.iter_mut() {
+        script.native_instr_limit = match script
+            .owner_id
+            .and_then(|owner_id| unreserved.get_mut(&owner_id))
+        {
+            Some(unreserved) => {
+                let limit = script.max_instr.min(*unreserved);
+                *unreserved -= limit;
+                limit
+            }
+            None => script.max_instr,
+        };
+    }
+    let usage = cpu
+        .into_iter()
+        .map(|(user_id, (available, remaining))| CpuUsageIntent {
+            user_id,
+            instructions: available - remaining,
+        })
+        .collect();
+    (scheduled, usage)
+}
+
 pub fn execute_scripts(
     workload: &[(EntityId, EntityScript)],
     storage: &World,
-) -> Result<Vec<BotIntents>, Infallible> {
+) -> Result<RunResult, Infallible> {
     profile!("execute_scripts");
 
-    let owners_table = storage.view::<EntityId, OwnedEntity>().reborrow();
+    let time = storage.time();
 
-    let n_scripts = workload.len();
+    let (scheduled, cpu_usage) = schedule_scripts(workload, storage);
+    let n_scripts = scheduled.len();
+    let num_scripts_skipped = (workload.len() - n_scripts) as u64;
 
     let chunk_size = n_scripts.clamp(8, 256);
 
     debug!(
-        "Executing {} scripts in chunks of {}",
-        n_scripts, chunk_size
+        "Executing {} scripts in chunks of {}, skipping {}",
+        n_scripts, chunk_size, num_scripts_skipped
     );
 
-    #[derive(Default)]
-    struct RunResult {
-        intents: Vec<BotIntents>,
-        num_scripts_ran: u64,
-        num_scripts_errored: u64,
-    }
-
-    let run_result = workload
+    let mut run_result = scheduled
         .par_iter()
         .chunks(chunk_size)
         .map(|entity_scripts| {
             let mut results = RunResult {
                 intents: Vec::with_capacity(chunk_size),
-                num_scripts_ran: 0,
-                num_scripts_errored: 0,
+                ..Default::default()
             };
             let data = ScriptExecutionData::new(
                 storage,
@@ -73,17 +196,19 @@ pub fn execute_scripts(
                 get_alloc(),
             );
 
-            let conf = UnwrapView::<ConfigKey, GameConfig>::from_world(storage);
             let mut vm = Vm::new(data).expect("Failed to initialize VM");
             vm.runtime_data.set_memory_limit(40 * 1024 * 1024);
-            vm.max_instr = conf.execution_limit as u64;
             crate::scripting_api::make_import().execute_imports(&mut vm);
 
-            for (entity_id, script) in entity_scripts {
-                let owner_id = owners_table
-                    .get(*entity_id)
-                    .map(|OwnedEntity { owner_id }| *owner_id);
-
+            for ScheduledScript {
+                entity_id,
+                script,
+                owner_id,
+                max_instr,
+                cpu_remaining,
+                native_instr_limit,
+            } in entity_scripts
+            {
                 let s = tracing::error_span!(
                     "script_execution",
                     entity_id = entity_id.to_string().as_str()
@@ -91,14 +216,37 @@ pub fn execute_scripts(
                 let _e = s.enter();
 
                 vm.clear();
-                match execute_single_script(*entity_id, script.0, owner_id, storage, &mut vm) {
-                    Ok(ints) => results.intents.push(ints),
+                vm.max_instr = *max_instr;
+                vm.auxiliary_data.cpu_remaining = *cpu_remaining;
+                vm.auxiliary_data.native_instr_limit = *native_instr_limit;
+                let result =
+                    execute_single_script(*entity_id, script.0, *owner_id, storage, &mut vm);
+                let native_instr = vm.auxiliary_data.native_instr;
+                let cpu_usage_intent =
+                    owner_id
+                        .filter(|_| native_instr > 0)
+                        .map(|user_id| CpuUsageIntent {
+                            user_id,
+                            instructions: native_instr,
+                        });
+                match result {
+                    Ok(mut ints) => {
+                        ints.cpu_usage_intent = cpu_usage_intent;
+                        results.intents.push(ints);
+                    }
                     Err(err) => {
                         results.num_scripts_errored += 1;
                         debug!(
                             "Execution failure in {:?} of {:?}:\n{:?}",
                             script, entity_id, err
                         );
+                        // the intents of failed scripts are discarded, only report the error
+                        results.intents.push(BotIntents {
+                            entity_id: *entity_id,
+                            script_error_intent: Some(err.to_script_error(time, *entity_id)),
+                            cpu_usage_intent,
+                            ..Default::default()
+                        });
                     }
                 }
                 results.num_scripts_ran += 1;
@@ -112,12 +260,22 @@ pub fn execute_scripts(
             res
         });
 
+    run_result.num_scripts_skipped = num_scripts_skipped;
+    run_result
+        .intents
+        .extend(cpu_usage.into_iter().map(|intent| BotIntents {
+            cpu_usage_intent: Some(intent),
+            ..Default::default()
+        }));
+
     debug!(
-        "Executing scripts done. Returning {:?} intents",
-        run_result.intents.len()
+        "Executing scripts done. Returning {:?} intents, {} of {} scripts failed",
+        run_result.intents.len(),
+        run_result.num_scripts_errored,
+        run_result.num_scripts_ran
     );
 
-    Ok(run_result.intents)
+    Ok(run_result)
 }
 
 pub(crate) fn get_alloc() -> Rc<RefCell<LinearAllocator>> {
@@ -152,7 +310,9 @@ pub fn execute_single_script<'a>(
         }
     })?;
 
-    let intents = std::mem::take(&mut vm.auxiliary_data.intents);
+    let memory_intent = vm.auxiliary_data.take_memory_intent();
+    let mut intents = std::mem::take(&mut vm.auxiliary_data.intents);
+    intents.memory_intent = memory_intent;
     trace!("Script execution completed, intents:{:?}", intents);
     Ok(intents)
 }
@@ -160,6 +320,24 @@ pub fn execute_single_script<'a>(
 pub struct ScriptExecutionData {
     pub entity_id: EntityId,
     pub user_id: Option<UserId>,
+    /// Cpu budget of the user left for the rest of the tick. `None` for unowned entities
+    pub cpu_remaining: Option<u64>,
+    /// Instructions used by native functions, like path finding, in the current script.
+    ///
+    /// Native functions can not consume the instructions of the Vm, so they are limited by
+    /// `native_instr_limit` instead, and charged to the owner on top of the script's reservation.
+    pub native_instr: u64,
+    /// Instructions native functions may use in the current script, reserved from the budget
+    /// of the owner
+    pub native_instr_limit: u64,
+    /// Memory of the entity, copied from the storage on the first write. Use
+    /// [memory](Self::memory) to read it
+    pub memory: Option<ScriptMemory>,
+    pub memory_changed: bool,
+    /// Memory of the owner, loaded on first access
+    pub user_memory: Option<ScriptMemory>,
+    /// Keys of the owner's memory set by the script, in order
+    pub user_memory_changes: Vec<(String, Option<MemoryValue>)>,
     pub intents: BotIntents,
     pub alloc: Rc<RefCell<LinearAllocator>>,
     storage: *const World,
@@ -180,6 +358,66 @@ impl ScriptExecutionData {
         self.intents.entity_id = entity_id;
         self.entity_id = entity_id;
         self.user_id = user_id;
+        self.native_instr = 0;
+        self.memory = None;
+        self.memory_changed = false;
+        self.user_memory = None;
+        self.user_memory_changes.clear();
+    }
+
+    /// Memory of the entity, as seen by this script. `None` if it has no memory
+    pub fn memory(&self) -> Option<&ScriptMemory> {
+        match self.memory.as_ref() {
+            Some(memory) => Some(memory),
+            None => self
+                .storage()
+                .view::<EntityId, ScriptMemory>()
+                .reborrow()
+                .get(self.entity_id),
+        }
+    }
+
+    /// Memory of the entity, copied from the storage on the first call
+    pub fn memory_mut(&mut self) -> &mut ScriptMemory {
+        if self.memory.is_none() {
+            let memory = self.memory().cloned().unwrap_or_default();
+            self.memory = Some(memory);
+        }
+        self.memory.get_or_insert_with(Default::default)
+    }
+
+    /// Memory of the owner of the entity, as seen by this script. `None` for unowned entities
+    pub fn user_memory_mut(&mut self) -> Option<&mut ScriptMemory> {
+        let user_id = self.user_id?;
+        if self.user_memory.is_none() {
+            let memory = self
+                .storage()
+                .view::<UserId, ScriptMemory>()
+                .get(user_id)
+                .cloned()
+                .unwrap_or_default();
+            self.user_memory = Some(memory);
+        }
+        self.user_memory.as_mut()
+    }
+
+    /// Changes to the memories made by the script, if any
+    pub fn take_memory_intent(&mut self) -> Option<MemoryIntent> {
+        if !self.memory_changed && self.user_memory_changes.is_empty() {
+            return None;
+        }
+        let entity_memory = if self.memory_changed {
+            self.memory.take()
+        } else {
+            None
+        };
+        self.memory_changed = false;
+        Some(MemoryIntent {
+            entity_id: self.entity_id,
+            entity_memory,
+            user_id: self.user_id,
+            user_memory_changes: std::mem::take(&mut self.user_memory_changes),
+        })
     }
 
     pub fn new(
@@ -194,6 +432,13 @@ impl ScriptExecutionData {
             intents,
             entity_id,
             user_id,
+            cpu_remaining: None,
+            native_instr: 0,
+            native_instr_limit: 0,
+            memory: None,
+            memory_changed: false,
+            user_memory: None,
+            user_memory_changes: Vec::new(),
             alloc,
         }
     }
@@ -202,3 +447,50 @@ impl ScriptExecutionData {
         unsafe { &*self.storage }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query;
+
+    #[test]
+    fn test_native_instructions_are_limited_by_the_unreserved_budget() {
+        let mut world = World::new();
+        {
+            let mut config = world.unsafe_view::<ConfigKey, GameConfig>();
+            let config = config.unwrap_mut();
+            config.execution_limit = 100;
+            config.cpu_per_level = 330;
+        }
+
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let owned = (0..3).map(|_| world.insert_entity()).collect::<Vec<_>>();
+        let unowned = world.insert_entity();
+        for entity_id in owned.iter() {
+            query!(
+                mutate
+                world
+                {
+                    EntityId, OwnedEntity, .insert(*entity_id, OwnedEntity { owner_id: user_id });
+                }
+            );
+        }
+
+        let workload = owned
+            .iter()
+            .chain(std::iter::once(&unowned))
+            .map(|entity_id| (*entity_id, EntityScript::default()))
+            .collect::<Vec<_>>();
+        let (scheduled, usage) = schedule_scripts(workload.as_slice(), &world);
+
+        let limits = scheduled
+            .iter()
+            .map(|script| (script.max_instr, script.native_instr_limit))
+            .collect::<Vec<_>>();
+        // the owner has 30 instructions left after reserving its scripts, the first script may
+        // use all of them
+        assert_eq!(limits, vec![(100, 30), (100, 0), (100, 0), (100, 100)]);
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].instructions, 300);
+    }
+}
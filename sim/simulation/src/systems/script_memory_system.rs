@@ -0,0 +1,106 @@
+use crate::components::{game_config::GameConfig, ScriptMemory};
+use crate::indices::*;
+use crate::intents::{Intents, MemoryIntent};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapView, UnwrapViewMut};
+use crate::tables::Table;
+use std::mem::take;
+use tracing::trace;
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<MemoryIntent>>,
+    UnsafeView<EntityId, ScriptMemory>,
+    UnsafeView<UserId, ScriptMemory>,
+);
+type Const<'a> = (UnwrapView<'a, ConfigKey, GameConfig>,);
+
+/// Write back the memories scripts changed in this tick
+///
+/// The changes of user memories are applied in intent order. Changes that would exceed the
+/// memory limit are dropped.
+pub fn script_memory_update(
+    (mut intents, mut entity_memory, mut user_memory): Mut,
+    (config,): Const,
+) {
+    profile!("ScriptMemorySystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for intent in intents {
+        trace!("Updating script memory {:?}", intent);
+        if let Some(memory) = intent.entity_memory {
+            if memory.0.is_empty() {
+                entity_memory.delete(intent.entity_id);
+            } else {
+                entity_memory.insert(intent.entity_id, memory);
+            }
+        }
+        let user_id = match intent.user_id {
+            Some(user_id) if !intent.user_memory_changes.is_empty() => user_id,
+            _ => continue,
+        };
+        let mut memory = user_memory.get(user_id).cloned().unwrap_or_default();
+        for (key, value) in intent.user_memory_changes {
+            if !memory.set(key, value, config.user_memory_limit) {
+                trace!("User {:?} memory is full", user_id);
+            }
+        }
+        user_memory.insert(user_id, memory);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::MemoryValue;
+    use crate::prelude::*;
+    use crate::storage::views::{FromWorld, FromWorldMut};
+
+    #[test]
+    fn test_user_memory_changes_respect_the_limit() {
+        let mut world = World::new();
+        world
+            .unsafe_view::<ConfigKey, GameConfig>()
+            .unwrap_mut()
+            .user_memory_limit = 20;
+
+        let user_id = UserId(uuid::Uuid::new_v4());
+        let entity_id = world.insert_entity();
+        let mut memory = ScriptMemory::default();
+        memory
+            .0
+            .insert("role".to_owned(), MemoryValue::Text("miner".to_owned()));
+
+        let set = |key: &str, value: i64| (key.to_owned(), Some(MemoryValue::Integer(value)));
+        *UnwrapViewMut::<EmptyKey, Intents<MemoryIntent>>::from_world_mut(&mut world) =
+            Intents(vec![
+                MemoryIntent {
+                    entity_id,
+                    entity_memory: Some(memory.clone()),
+                    user_id: Some(user_id),
+                    user_memory_changes: vec![set("a", 1), set("b", 2)],
+                },
+                MemoryIntent {
+                    entity_id,
+                    entity_memory: None,
+                    user_id: Some(user_id),
+                    // `c` would not fit, overwriting `a` does
+                    user_memory_changes: vec![set("c", 3), set("a", 4)],
+                },
+            ]);
+
+        script_memory_update(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+
+        assert_eq!(
+            world.view::<EntityId, ScriptMemory>().get(entity_id),
+            Some(&memory)
+        );
+        let user_memory = world.view::<UserId, ScriptMemory>();
+        let user_memory = &user_memory.get(user_id).unwrap().0;
+        assert_eq!(user_memory.len(), 2);
+        assert_eq!(user_memory.get("a"), Some(&MemoryValue::Integer(4)));
+        assert_eq!(user_memory.get("c"), None);
+    }
+}
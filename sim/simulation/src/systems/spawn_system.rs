@@ -2,18 +2,24 @@
 //!
 //! - Spawn Intent will add a bot spawn task to the queue if it isn't full
 //! - Spawn update will first decrement time to spawn and spawn the bot if it reaches 0
-//! - If time to spawn is 0 and the queue is not empty start another spawn process
+//! - If time to spawn is 0, the queue is not empty and the spawn has enough energy to pay for
+//!   the next bot's body start another spawn process
 //!
 mod continous_spawn_system;
+mod continuous_spawn_intent_system;
 mod spawn_intent_system;
+mod spawn_script_intent_system;
 
 pub use continous_spawn_system::update as update_cont_spawns;
+pub use continuous_spawn_intent_system::update as update_continuous_spawn_intents;
 pub use spawn_intent_system::update as update_spawn_intents;
+pub use spawn_script_intent_system::update as update_spawn_script_intents;
 
-use crate::indices::{EntityId, UserId};
+use crate::components::game_config::GameConfig;
+use crate::indices::{ConfigKey, EntityId, ScriptId, UserId};
 use crate::join;
 use crate::profile;
-use crate::storage::views::{UnsafeView, View};
+use crate::storage::views::{UnsafeView, UnwrapView, View};
 use crate::tables::{JoinIterator, Table};
 use crate::{components::*, entity_archetypes::init_bot};
 use tracing::{trace, warn};
@@ -28,28 +34,51 @@ type SpawnSystemMut = (
         UnsafeView<EntityId, HpComponent>,
         UnsafeView<EntityId, DecayComponent>,
         UnsafeView<EntityId, CarryComponent>,
+        (
+            UnsafeView<EntityId, MeleeAttackComponent>,
+            UnsafeView<EntityId, RangedAttackComponent>,
+            UnsafeView<EntityId, ArmorComponent>,
+            UnsafeView<EntityId, HealComponent>,
+        ),
         UnsafeView<EntityId, PositionComponent>,
         UnsafeView<EntityId, OwnedEntity>,
         UnsafeView<EntityId, EntityScript>,
     ),
 );
 
+type SpawnSystemConst<'a> = (
+    View<'a, UserId, EntityScript>,
+    UnwrapView<'a, ConfigKey, GameConfig>,
+);
+
 pub fn update_spawns(
     (mut spawns, mut spawn_queue, mut energy, spawn_views): SpawnSystemMut,
-    user_default_scripts: View<UserId, EntityScript>,
+    (user_default_scripts, config): SpawnSystemConst,
 ) {
     profile!("SpawnSystem update");
 
+    let spawn_bots = spawn_views.0;
     let ss = spawns.iter_mut().filter(|(_, c)| c.spawning.is_none());
-    let en = energy.iter_mut().filter(|(_, e)| e.energy == e.energy_max);
+    let en = energy.iter_mut();
     let sq = spawn_queue.iter_mut();
     join!([ss, en, sq]).for_each(|(_spawn_id, (spawn, energy, queue))| {
-        // spawns with 500 energy and no currently spawning bot
-        if let Some(bot) = queue.queue.pop_back() {
-            energy.energy = 0;
-            spawn.time_to_spawn = 10;
-            spawn.spawning = Some(bot);
+        // spawns with no currently spawning bot
+        let bot = match queue.queue.back() {
+            Some(bot) => *bot,
+            None => return,
+        };
+        let body = spawn_bots
+            .get(bot)
+            .map(|SpawnBotComponent { body, .. }| *body)
+            .unwrap_or_default();
+        let cost = config.bot_body_cost.cost(&body);
+        if (energy.energy as u32) < cost {
+            return;
         }
+        queue.queue.pop_back();
+        energy.energy -= cost as u16;
+        spawn.time_to_spawn = 10;
+        spawn.spawning = Some(bot);
     });
 
     spawns
@@ -58,13 +87,23 @@ pub fn update_spawns(
         .filter_map(|(spawn_id, spawn_component)| {
             spawn_component.time_to_spawn -= 1;
             if spawn_component.time_to_spawn == 0 {
-                spawn_component.spawning.take().map(|b| (spawn_id, b))
+                let script = spawn_component.bot_script;
+                spawn_component
+                    .spawning
+                    .take()
+                    .map(|b| (spawn_id, b, script))
             } else {
                 None
             }
         })
-        .for_each(|(spawn_id, entity_id)| {
-            spawn_bot(spawn_id, entity_id, spawn_views, user_default_scripts)
+        .for_each(|(spawn_id, entity_id, script)| {
+            spawn_bot(
+                spawn_id,
+                entity_id,
+                script,
+                spawn_views,
+                user_default_scripts,
+            )
         });
 }
 
@@ -74,17 +113,25 @@ type SpawnBotMut = (
     UnsafeView<EntityId, HpComponent>,
     UnsafeView<EntityId, DecayComponent>,
     UnsafeView<EntityId, CarryComponent>,
+    (
+        UnsafeView<EntityId, MeleeAttackComponent>,
+        UnsafeView<EntityId, RangedAttackComponent>,
+        UnsafeView<EntityId, ArmorComponent>,
+        UnsafeView<EntityId, HealComponent>,
+    ),
     UnsafeView<EntityId, PositionComponent>,
     UnsafeView<EntityId, OwnedEntity>,
     UnsafeView<EntityId, EntityScript>,
 );
 
 /// Spawns a bot from a spawn.
-/// Removes the spawning bot from the spawn and initializes a bot in the world
+/// Removes the spawning bot from the spawn and initializes a bot in the world.
+/// `script` overrides the default script of the owner
 fn spawn_bot(
     spawn_id: EntityId,
     entity_id: EntityId,
-    (mut spawn_bots, bots, hps, decay, carry, positions, owned, script_table): SpawnBotMut,
+    script: Option<ScriptId>,
+    (mut spawn_bots, bots, hps, decay, carry, combat, positions, owned, mut script_table): SpawnBotMut,
     user_default_scripts: View<UserId, EntityScript>,
 ) {
     trace!(
@@ -93,8 +140,8 @@ fn spawn_bot(
         entity_id
     );
 
-    match spawn_bots.delete(entity_id) {
-        Some(_) => (),
+    let body = match spawn_bots.delete(entity_id) {
+        Some(SpawnBotComponent { body, .. }) => body,
         None => {
             warn!("Spawning bot {:?} was not found", entity_id);
             return;
@@ -115,9 +162,22 @@ fn spawn_bot(
         entity_id,
         owner,
         pos,
-        (bots, hps, decay, carry, positions, owned, script_table),
+        body,
+        (
+            bots,
+            hps,
+            decay,
+            carry,
+            combat,
+            positions,
+            owned,
+            script_table,
+        ),
         user_default_scripts,
     );
+    if let Some(script) = script {
+        script_table.insert(entity_id, EntityScript(script));
+    }
 
     trace!(
         "spawn_bot spawn_id: {:?} entity_id: {:?} - done",
@@ -125,3 +185,104 @@ fn spawn_bot(
         entity_id
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::query;
+
+    #[test]
+    fn test_spawning_waits_for_the_cost_of_the_body() {
+        let mut world = World::new();
+        let spawn_id = world.insert_entity();
+        let bot_id = world.insert_entity();
+        let body = BotBody {
+            hp: 10,
+            carry: 0,
+            melee: 20,
+            ranged: 0,
+            armor: 5,
+            heal: 0,
+            decay_interval: 4,
+        };
+        let cost = GameConfig::default().bot_body_cost.cost(&body) as u16;
+        query!(
+            mutate
+            world
+            {
+                EntityId, SpawnComponent, .insert(spawn_id, SpawnComponent::default());
+                EntityId, SpawnQueueComponent, .insert(
+                    spawn_id,
+                    SpawnQueueComponent {
+                        queue: vec![bot_id].into(),
+                    }
+                );
+                EntityId, EnergyComponent, .insert(
+                    spawn_id,
+                    EnergyComponent {
+                        energy: cost - 1,
+                        energy_max: 500,
+                    }
+                );
+                EntityId, PositionComponent, .insert(spawn_id, Default::default());
+                EntityId, SpawnBotComponent, .insert(bot_id, SpawnBotComponent { bot: Bot, body });
+            }
+        );
+
+        update_spawns(
+            FromWorldMut::from_world_mut(&mut world),
+            FromWorld::from_world(&world),
+        );
+        assert!(world
+            .view::<EntityId, SpawnComponent>()
+            .get(spawn_id)
+            .unwrap()
+            .spawning
+            .is_none());
+
+        world
+            .unsafe_view::<EntityId, EnergyComponent>()
+            .get_mut(spawn_id)
+            .unwrap()
+            .energy = cost + 1;
+        for _ in 0..10 {
+            update_spawns(
+                FromWorldMut::from_world_mut(&mut world),
+                FromWorld::from_world(&world),
+            );
+        }
+
+        assert_eq!(
+            world
+                .view::<EntityId, EnergyComponent>()
+                .get(spawn_id)
+                .unwrap()
+                .energy,
+            1
+        );
+        assert!(world.view::<EntityId, Bot>().contains(&bot_id));
+        assert_eq!(
+            world
+                .view::<EntityId, MeleeAttackComponent>()
+                .get(bot_id)
+                .unwrap()
+                .strength,
+            20
+        );
+        assert!(world
+            .view::<EntityId, RangedAttackComponent>()
+            .get(bot_id)
+            .is_none());
+        assert_eq!(
+            world
+                .view::<EntityId, ArmorComponent>()
+                .get(bot_id)
+                .unwrap()
+                .armor,
+            5
+        );
+        let decay = world.view::<EntityId, DecayComponent>();
+        assert_eq!(decay.get(bot_id).unwrap().interval, 4);
+    }
+}
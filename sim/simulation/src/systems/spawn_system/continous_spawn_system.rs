@@ -14,20 +14,24 @@ type SpawnSystemMut = (UnwrapViewMut<EmptyKey, Intents<SpawnIntent>>,);
 
 type SpawnSystemConsts<'a> = (
     View<'a, EntityId, OwnedEntity>,
+    View<'a, EntityId, SpawnComponent>,
     View<'a, EntityId, SpawnQueueComponent>,
 );
 
-pub fn update((mut intents,): SpawnSystemMut, (owners, spawn_queues): SpawnSystemConsts) {
+/// Queue a bot with the default body in continuous spawns with empty queues
+pub fn update((mut intents,): SpawnSystemMut, (owners, spawns, spawn_queues): SpawnSystemConsts) {
     profile!("Continous Spawn System update");
 
+    let spawn_it = spawns.iter().filter(|(_, s)| s.continuous);
     let spawnq_it = spawn_queues.iter().filter(|(_, q)| q.queue.is_empty());
     let own_it = owners.iter();
 
-    for (spawn_id, (_spawn, owner)) in join!([spawnq_it, own_it]) {
+    for (spawn_id, (_spawn, _queue, owner)) in join!([spawn_it, spawnq_it, own_it]) {
         trace!("Adding a spawn intent to the queue of spawn {:?}", spawn_id);
         intents.0.push(SpawnIntent {
             spawn_id,
             owner_id: Some(owner.owner_id),
+            body: BotBody::default(),
         });
     }
 }
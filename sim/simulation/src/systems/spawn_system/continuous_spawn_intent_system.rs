@@ -0,0 +1,30 @@
+use crate::components::SpawnComponent;
+use crate::indices::*;
+use crate::intents::{ContinuousSpawnIntent, Intents};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut};
+use std::mem::take;
+use tracing::{debug, trace};
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<ContinuousSpawnIntent>>,
+    UnsafeView<EntityId, SpawnComponent>,
+);
+
+/// Turn continuous spawning on or off. The last intent of a spawn wins
+pub fn update((mut intents, mut spawns): Mut, (): ()) {
+    profile!("ContinuousSpawnIntentSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for ContinuousSpawnIntent { spawn_id, enabled } in intents {
+        trace!(
+            "Setting continuous spawning of {:?} to {}",
+            spawn_id,
+            enabled
+        );
+        match spawns.get_mut(spawn_id) {
+            Some(spawn) => spawn.continuous = enabled,
+            None => debug!("Spawn {:?} was not found", spawn_id),
+        }
+    }
+}
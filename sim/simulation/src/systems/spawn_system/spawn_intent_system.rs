@@ -1,6 +1,6 @@
 use crate::components::{Bot, OwnedEntity, SpawnBotComponent, SpawnQueueComponent};
 use crate::indices::*;
-use crate::intents::{Intents, SpawnIntent};
+use crate::intents::{Intents, SpawnIntent, SPAWN_QUEUE_LIMIT};
 use crate::profile;
 use crate::storage::views::{InsertEntityView, UnsafeView, UnwrapView};
 use tracing::{debug, trace};
@@ -36,14 +36,19 @@ pub fn update(
                 continue;
             }
         };
-        if spawn.queue.len() > 20 {
-            // TODO: config
+        if spawn.queue.len() >= SPAWN_QUEUE_LIMIT {
             debug!("spawn queue is full");
             continue;
         }
 
         let bot_id = unsafe { insert_entity.insert_entity() };
-        spawn_bot_table.insert(bot_id, SpawnBotComponent { bot: Bot {} });
+        spawn_bot_table.insert(
+            bot_id,
+            SpawnBotComponent {
+                bot: Bot {},
+                body: intent.body,
+            },
+        );
         if let Some(owner_id) = intent.owner_id {
             owner_table.insert(bot_id, OwnedEntity { owner_id });
         }
@@ -0,0 +1,34 @@
+use crate::components::SpawnComponent;
+use crate::indices::*;
+use crate::intents::{Intents, SpawnScriptIntent};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut};
+use std::mem::take;
+use tracing::{debug, trace};
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<SpawnScriptIntent>>,
+    UnsafeView<EntityId, SpawnComponent>,
+);
+
+/// Set the scripts of the bots spawned by spawns. The last intent of a spawn wins
+pub fn update((mut intents, mut spawns): Mut, (): ()) {
+    profile!("SpawnScriptIntentSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for SpawnScriptIntent {
+        spawn_id,
+        script_id,
+    } in intents
+    {
+        trace!(
+            "Setting the bot script of {:?} to {:?}",
+            spawn_id,
+            script_id
+        );
+        match spawns.get_mut(spawn_id) {
+            Some(spawn) => spawn.bot_script = script_id,
+            None => debug!("Spawn {:?} was not found", spawn_id),
+        }
+    }
+}
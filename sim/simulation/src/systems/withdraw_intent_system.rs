@@ -0,0 +1,87 @@
+use crate::components::{CarryComponent, InventoryComponent};
+use crate::indices::*;
+use crate::intents::{Intents, WithdrawIntent};
+use crate::profile;
+use crate::storage::views::{UnsafeView, UnwrapViewMut};
+use std::mem::take;
+use tracing::{trace, warn};
+
+type Mut = (
+    UnwrapViewMut<EmptyKey, Intents<WithdrawIntent>>,
+    UnsafeView<EntityId, InventoryComponent>,
+    UnsafeView<EntityId, CarryComponent>,
+);
+
+/// Withdraw resources in intent order
+pub fn withdraw_intents_update((mut intents, mut inventory_table, mut carry_table): Mut, (): ()) {
+    profile!("WithdrawSystem update");
+
+    let Intents(intents) = take(&mut *intents);
+    for intent in intents {
+        trace!("Executing withdraw intent {:?}", intent);
+        let carry = match carry_table.get_mut(intent.bot) {
+            Some(x) => x,
+            None => {
+                warn!("Bot has no carry");
+                continue;
+            }
+        };
+        let inventory = match inventory_table.get_mut(intent.structure) {
+            Some(x) => x,
+            None => {
+                warn!("Structure has no inventory");
+                continue;
+            }
+        };
+        // withdraw amount = min(amount, stored, bot capacity)
+        let amount = intent.amount.min(carry.free());
+        let amount = inventory.resources.remove(intent.ty, amount);
+        carry.add(intent.ty, amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Resource;
+    use crate::prelude::*;
+    use crate::query;
+
+    #[test]
+    fn test_withdraw_is_limited_by_the_carry() {
+        let mut world = World::new();
+        let bot = world.insert_entity();
+        let container = world.insert_entity();
+        let mut inventory = InventoryComponent::new(1000);
+        inventory.add(Resource::Mineral, 100);
+        inventory.add(Resource::Energy, 100);
+        let mut carry = CarryComponent::new(50);
+        carry.add(Resource::Energy, 20);
+        query!(
+            mutate
+            world
+            {
+                EntityId, InventoryComponent, .insert(container, inventory);
+                EntityId, CarryComponent, .insert(bot, carry);
+            }
+        );
+        *UnwrapViewMut::<EmptyKey, Intents<WithdrawIntent>>::from_world_mut(&mut world) =
+            Intents(vec![WithdrawIntent {
+                bot,
+                structure: container,
+                amount: 40,
+                ty: Resource::Mineral,
+            }]);
+
+        withdraw_intents_update(FromWorldMut::from_world_mut(&mut world), ());
+
+        let carry = world.view::<EntityId, CarryComponent>();
+        let carry = carry.get(bot).unwrap();
+        assert_eq!(carry.resources.get(Resource::Mineral), 30);
+        assert_eq!(carry.free(), 0);
+        let inventory = world.view::<EntityId, InventoryComponent>();
+        let inventory = inventory.get(container).unwrap();
+        assert_eq!(inventory.resources.get(Resource::Mineral), 70);
+        assert_eq!(inventory.resources.get(Resource::Energy), 100);
+    }
+}
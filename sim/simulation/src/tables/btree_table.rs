@@ -10,9 +10,39 @@ where
     Id: TableId,
     Row: TableRow,
 {
+    /// Serialized as a list of pairs, because formats like JSON only support string keys
+    #[serde(with = "pairs")]
+    #[serde(bound(
+        serialize = "Row: Serialize",
+        deserialize = "Id: Deserialize<'de>, Row: Deserialize<'de>"
+    ))]
     data: BTreeMap<Id, Row>,
 }
 
+mod pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S, Id, Row>(data: &BTreeMap<Id, Row>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        Id: Serialize,
+        Row: Serialize,
+    {
+        serializer.collect_seq(data.iter())
+    }
+
+    pub fn deserialize<'de, D, Id, Row>(deserializer: D) -> Result<BTreeMap<Id, Row>, D::Error>
+    where
+        D: Deserializer<'de>,
+        Id: Deserialize<'de> + Ord,
+        Row: Deserialize<'de>,
+    {
+        let pairs: Vec<(Id, Row)> = Deserialize::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
 impl<Id, Row> BTreeTable<Id, Row>
 where
     Id: TableId,
@@ -95,3 +125,31 @@ impl LogTable for BTreeTable<EntityTime, LogEntry> {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indices::EntityId;
+
+    #[test]
+    fn test_json_round_trip_with_struct_keys() {
+        let mut table = BTreeTable::<EntityTime, LogEntry>::new();
+        table.insert(
+            EntityTime(EntityId::new(1, 0), 42),
+            LogEntry {
+                payload: "winnie".to_owned(),
+            },
+        );
+
+        let payload = serde_json::to_string(&table).unwrap();
+        let restored: BTreeTable<EntityTime, LogEntry> = serde_json::from_str(&payload).unwrap();
+
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            restored
+                .get(EntityTime(EntityId::new(1, 0), 42))
+                .map(|l| l.payload.as_str()),
+            Some("winnie")
+        );
+    }
+}
@@ -4,6 +4,11 @@ use std::{
     ptr,
 };
 
+use serde::{
+    de::{self, Deserialize, Deserializer},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
+
 use crate::indices::EntityId;
 
 pub struct HandleTable {
@@ -136,12 +141,90 @@ impl Drop for HandleTable {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
 struct Entry {
     data: u32,
     gen: u32,
 }
 
+impl Serialize for HandleTable {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // entries after the last touched one are still in their initial state, no need to save
+        // them
+        let entries = self.entries();
+        let len = (0..entries.len())
+            .rev()
+            .find(|&i| entries[i].data != i as u32 + 1 || entries[i].gen != 0)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        let mut state = serializer.serialize_struct("HandleTable", 3)?;
+        state.serialize_field("cap", &self.cap)?;
+        state.serialize_field("freeList", &self.free_list)?;
+        state.serialize_field("entries", &entries[..len])?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HandleTableSnapshot {
+    cap: u32,
+    free_list: u32,
+    entries: Vec<Entry>,
+}
+
+impl<'de> Deserialize<'de> for HandleTable {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let HandleTableSnapshot {
+            cap,
+            free_list,
+            entries,
+        } = HandleTableSnapshot::deserialize(deserializer)?;
+        if entries.len() > cap as usize {
+            return Err(de::Error::custom(format!(
+                "HandleTable has more entries ({}) than its capacity ({})",
+                entries.len(),
+                cap
+            )));
+        }
+        let mut result = HandleTable::new(cap);
+        unsafe {
+            for (i, entry) in entries.into_iter().enumerate() {
+                ptr::write(result.entries.add(i), entry);
+            }
+        }
+        // alloc follows the free list without bounds checks, so it must stay inside the table and
+        // must not loop. `cap` ends the list once every entry was handed out
+        let mut visited = vec![false; cap as usize];
+        let mut next = free_list;
+        while next != SENTINEL && next != cap {
+            if next > cap {
+                return Err(de::Error::custom(format!(
+                    "HandleTable free list links to index {} outside of its capacity ({})",
+                    next, cap
+                )));
+            }
+            if visited[next as usize] {
+                return Err(de::Error::custom(format!(
+                    "HandleTable free list has a cycle at index {}",
+                    next
+                )));
+            }
+            visited[next as usize] = true;
+            next = result.entries()[next as usize].data;
+        }
+        result.free_list = free_list;
+        Ok(result)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +245,40 @@ mod tests {
             let _e = table.alloc();
         }
     }
+
+    #[test]
+    fn test_de_serialize() {
+        let mut table = HandleTable::new(512);
+
+        let ids = (0..8).map(|_| table.alloc()).collect::<Vec<_>>();
+        table.free(ids[2]);
+        table.free(ids[5]);
+
+        let s = serde_json::to_string(&table).unwrap();
+        let mut res: HandleTable = serde_json::from_str(s.as_str()).unwrap();
+
+        for (i, id) in ids.iter().enumerate() {
+            assert_eq!(table.is_valid(*id), res.is_valid(*id), "{}", i);
+        }
+        // the free list should be restored as well
+        assert_eq!(table.alloc(), res.alloc());
+        assert_eq!(table.alloc(), res.alloc());
+        assert_eq!(table.alloc(), res.alloc());
+    }
+
+    #[test]
+    fn test_deserialize_rejects_corrupt_free_list() {
+        let out_of_bounds_head = r#"{"cap":4,"freeList":7,"entries":[]}"#;
+        let out_of_bounds_link = r#"{"cap":4,"freeList":0,"entries":[{"data":100,"gen":0}]}"#;
+        let cycle = r#"{"cap":4,"freeList":0,"entries":[{"data":1,"gen":0},{"data":0,"gen":0}]}"#;
+
+        for s in [out_of_bounds_head, out_of_bounds_link, cycle] {
+            let res = serde_json::from_str::<HandleTable>(s);
+            assert!(res.is_err(), "{}", s);
+        }
+
+        // a table with every handle allocated is still valid
+        let full = r#"{"cap":2,"freeList":2,"entries":[{"data":0,"gen":0},{"data":0,"gen":0}]}"#;
+        serde_json::from_str::<HandleTable>(full).unwrap();
+    }
 }
@@ -1,7 +1,8 @@
 use super::{HexGrid, TableRow};
 use crate::prelude::Hexagon;
-use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
 
@@ -48,6 +49,22 @@ where
         )
     }
 
+    /// Non self-describing formats (e.g. bincode) hand us the fields in order
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let radius: i32 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let values: Vec<Row> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let radius = usize::try_from(radius)
+            .map_err(|_| de::Error::custom(format!("Invalid radius {}", radius)))?;
+        build_grid(radius, values)
+    }
+
     fn visit_map<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
@@ -70,26 +87,34 @@ where
         let radius = radius.ok_or_else(|| de::Error::missing_field("radius"))?;
         let values = values.ok_or_else(|| de::Error::missing_field("values"))?;
 
-        let mut result = HexGrid::new(radius);
+        build_grid(radius, values)
+    }
+}
 
-        let bounds = Hexagon::from_radius(radius as i32);
+fn build_grid<Row, E>(radius: usize, values: Vec<Row>) -> Result<HexGrid<Row>, E>
+where
+    Row: TableRow + Default,
+    E: de::Error,
+{
+    let mut result = HexGrid::new(radius);
 
-        let len = values.len();
-        if bounds.area() != len {
-            return Err(de::Error::custom(format!(
-                "Incorrect number of values were given. Expected: {}. Actual: {}.",
-                bounds.area(),
-                values.len()
-            )));
-        }
-        for (val, p) in values.into_iter().zip(bounds.iter_points()) {
-            result.insert(p, val).map_err(|_| {
-                de::Error::custom("Failed to insert value into HexGrid with given radius")
-            })?;
-        }
+    let bounds = Hexagon::from_radius(radius as i32);
 
-        Ok(result)
+    let len = values.len();
+    if bounds.area() != len {
+        return Err(de::Error::custom(format!(
+            "Incorrect number of values were given. Expected: {}. Actual: {}.",
+            bounds.area(),
+            values.len()
+        )));
+    }
+    for (val, p) in values.into_iter().zip(bounds.iter_points()) {
+        result.insert(p, val).map_err(|_| {
+            de::Error::custom("Failed to insert value into HexGrid with given radius")
+        })?;
     }
+
+    Ok(result)
 }
 
 impl<'de, Row> Deserialize<'de> for HexGrid<Row>
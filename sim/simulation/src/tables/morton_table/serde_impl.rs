@@ -1,7 +1,7 @@
 use super::{MortonTable, TableRow};
 use crate::prelude::Axial;
 use serde::{
-    de::{self, Deserialize, Deserializer, MapAccess, Visitor},
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
     ser::{Serialize, SerializeStruct, Serializer},
 };
 use std::fmt;
@@ -38,6 +38,17 @@ where
         formatter.write_str("a single 'values' field containing a list of [Axial, Row] tuples")
     }
 
+    /// Non self-describing formats (e.g. bincode) hand us the fields in order
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let values: Vec<(Axial, Row)> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        build_table(values)
+    }
+
     fn visit_map<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
     where
         V: MapAccess<'de>,
@@ -52,16 +63,24 @@ where
             }
         }
         let values = values.ok_or_else(|| de::Error::missing_field("values"))?;
-        let len = values.len();
-        MortonTable::from_vec(values).map_err(|e| {
-            de::Error::invalid_length(
-                len,
-                &format!("Failed to build MortonTable {:?}", e).as_str(),
-            )
-        })
+        build_table(values)
     }
 }
 
+fn build_table<Row, E>(values: Vec<(Axial, Row)>) -> Result<MortonTable<Row>, E>
+where
+    Row: TableRow + Default,
+    E: de::Error,
+{
+    let len = values.len();
+    MortonTable::from_vec(values).map_err(|e| {
+        de::Error::invalid_length(
+            len,
+            &format!("Failed to build MortonTable {:?}", e).as_str(),
+        )
+    })
+}
+
 impl<'de, Row> Deserialize<'de> for MortonTable<Row>
 where
     Row: TableRow + Deserialize<'de> + Default,
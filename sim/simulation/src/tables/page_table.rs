@@ -1,4 +1,5 @@
 mod pt_iter;
+mod serde_impl;
 
 use crate::prelude::EntityId;
 
@@ -0,0 +1,63 @@
+use super::PageTable;
+use crate::indices::EntityId;
+use serde::{
+    de::{Deserialize, Deserializer},
+    ser::{Serialize, Serializer},
+};
+
+impl<T> Serialize for PageTable<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T> Deserialize<'de> for PageTable<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values: Vec<(EntityId, T)> = Vec::deserialize(deserializer)?;
+        let mut result = PageTable::new(values.len());
+        for (id, value) in values {
+            result.insert(id, value);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_de_serialize() {
+        let mut table = PageTable::<i64>::new(0);
+
+        table.insert(EntityId { index: 12, gen: 3 }, 12);
+        table.insert(
+            EntityId {
+                index: 666,
+                gen: 12,
+            },
+            666,
+        );
+
+        let s = serde_json::to_string(&table).unwrap();
+        let res: PageTable<i64> = serde_json::from_str(s.as_str()).unwrap();
+
+        assert_eq!(res.len(), table.len());
+        for ((exp_id, exp), (act_id, act)) in table.iter().zip(res.iter()) {
+            assert_eq!(exp_id, act_id);
+            assert_eq!(exp, act);
+        }
+    }
+}
@@ -5,7 +5,7 @@ use super::*;
 use serde::{Deserialize, Serialize};
 use std::mem;
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UniqueTable<Id, Row>
 where
     Row: TableRow,
@@ -15,6 +15,19 @@ where
     _m: std::marker::PhantomData<Id>,
 }
 
+// derive(Default) would require `Row: Default`, but an empty table needs no default row
+impl<Id, Row> Default for UniqueTable<Id, Row>
+where
+    Row: TableRow,
+{
+    fn default() -> Self {
+        Self {
+            value: None,
+            _m: Default::default(),
+        }
+    }
+}
+
 impl<Id, Row> UniqueTable<Id, Row>
 where
     Row: TableRow,
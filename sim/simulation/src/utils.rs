@@ -1,8 +1,21 @@
-/// If `profile` feature is enabled, records high-level profiling information to `profile.csv`.
+/// If `profile` feature is enabled, records the duration of the enclosing scope under `$name`.
 ///
-#[macro_export(local_inner_macros)]
+/// Timings are aggregated per tick and written to `profile.csv`, see the
+/// [profile](crate::profile) module.
+#[cfg(feature = "profile")]
+#[macro_export]
 macro_rules! profile {
-    ($name: expr) => { 
-        // TODO
+    ($name: expr) => {
+        let _profile_timer = $crate::profile::Timer::new($name);
     };
 }
+
+/// If `profile` feature is enabled, records the duration of the enclosing scope under `$name`.
+///
+/// Timings are aggregated per tick and written to `profile.csv`, see the
+/// [profile](crate::profile) module.
+#[cfg(not(feature = "profile"))]
+#[macro_export]
+macro_rules! profile {
+    ($name: expr) => {};
+}
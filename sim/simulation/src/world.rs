@@ -1,5 +1,8 @@
+mod state_hash;
 mod world_serde;
 
+pub use state_hash::{StateHash, StateHasher};
+
 use crate::components::*;
 use crate::indices::*;
 use crate::intents::*;
@@ -20,6 +23,7 @@ use crate::tables::TableId;
 use crate::Time;
 use crate::{archetype, tables::hex_grid::HexGrid};
 use crate::{components::game_config::GameConfig, prelude::Axial};
+use rand::SeedableRng;
 
 archetype!(
     module room_store key Axial,
@@ -48,13 +52,21 @@ archetype!(
     table SpawnQueueComponent : PageTable<SpawnQueueComponent> = spawnqueue,
     table OwnedEntity : PageTable<OwnedEntity> = owner,
     table MeleeAttackComponent : PageTable<MeleeAttackComponent> = melee,
+    table RangedAttackComponent : PageTable<RangedAttackComponent> = ranged,
+    table ArmorComponent : PageTable<ArmorComponent> = armor,
+    table HealComponent : PageTable<HealComponent> = heal,
+    table ConstructionSiteComponent : PageTable<ConstructionSiteComponent> = construction_site,
+    table InventoryComponent : PageTable<InventoryComponent> = inventory,
     table SayComponent : PageTable<SayComponent> = say,
     table MineEventComponent : PageTable<MineEventComponent> = mine_intents,
     table DropoffEventComponent : PageTable<DropoffEventComponent> = dropoff_intents,
     table RespawnTimer : PageTable<RespawnTimer> = respawn_timer,
 
     table PathCacheComponent : PageTable<PathCacheComponent> = pathcache,
-    table ScriptHistory : PageTable<ScriptHistory> = script_history
+    table ScriptHistory : PageTable<ScriptHistory> = script_history,
+    table ScriptErrors : PageTable<ScriptErrors> = script_errors,
+    table ScriptMemory : PageTable<ScriptMemory> = script_memory,
+    table Inbox : PageTable<Inbox> = inbox
 
     iterby bot
     iterby structure
@@ -67,7 +79,11 @@ archetype!(
     table UserComponent : SparseFlagTable<UserId, UserComponent> = user,
     table EntityScript: BTreeTable<UserId, EntityScript> = user_default_script,
     table Rooms : BTreeTable<UserId, Rooms> = user_rooms,
-    table UserProperties : BTreeTable<UserId, UserProperties> = user_props
+    table UserProperties : BTreeTable<UserId, UserProperties> = user_props,
+    table ScriptErrors : BTreeTable<UserId, ScriptErrors> = user_script_errors,
+    table CpuBudget : BTreeTable<UserId, CpuBudget> = user_cpu,
+    table ScriptMemory : BTreeTable<UserId, ScriptMemory> = user_script_memory,
+    table UserChannels : BTreeTable<UserId, UserChannels> = user_channels
 
     iterby user
 );
@@ -76,16 +92,29 @@ archetype!(
     module resource_store key EmptyKey,
 
     table Time : UniqueTable<EmptyKey, Time> = time,
+    table WorldRng : UniqueTable<EmptyKey, WorldRng> = rng,
     table Intents<MoveIntent> : UniqueTable<EmptyKey, Intents<MoveIntent>> = move_intents,
     table Intents<SpawnIntent> : UniqueTable<EmptyKey, Intents<SpawnIntent>> = spawn_intents,
+    table Intents<ContinuousSpawnIntent> : UniqueTable<EmptyKey, Intents<ContinuousSpawnIntent>> = continuous_spawn_intents,
+    table Intents<SpawnScriptIntent> : UniqueTable<EmptyKey, Intents<SpawnScriptIntent>> = spawn_script_intents,
     table Intents<MineIntent> : UniqueTable<EmptyKey, Intents<MineIntent>> = mine_intents,
     table Intents<DropoffIntent> : UniqueTable<EmptyKey, Intents<DropoffIntent>> = dropoff_intents,
+    table Intents<WithdrawIntent> : UniqueTable<EmptyKey, Intents<WithdrawIntent>> = withdraw_intents,
+    table Intents<EnergyTransferIntent> : UniqueTable<EmptyKey, Intents<EnergyTransferIntent>> = energy_transfer_intents,
     table Intents<LogIntent> : UniqueTable<EmptyKey, Intents<LogIntent>> = log_intents,
     table Intents<CachePathIntent> : UniqueTable<EmptyKey, Intents<CachePathIntent>> = update_path_cache_intents,
     table Intents<MutPathCacheIntent> : UniqueTable<EmptyKey, Intents<MutPathCacheIntent>> = mut_path_cache_intents,
     table Intents<MeleeIntent> : UniqueTable<EmptyKey, Intents<MeleeIntent>> = melee_intents,
+    table Intents<RangedIntent> : UniqueTable<EmptyKey, Intents<RangedIntent>> = ranged_intents,
+    table Intents<HealIntent> : UniqueTable<EmptyKey, Intents<HealIntent>> = heal_intents,
+    table Intents<RepairIntent> : UniqueTable<EmptyKey, Intents<RepairIntent>> = repair_intents,
+    table Intents<BuildIntent> : UniqueTable<EmptyKey, Intents<BuildIntent>> = build_intents,
     table Intents<ScriptHistoryEntry> : UniqueTable<EmptyKey, Intents<ScriptHistoryEntry>> = script_history_intents,
-    table Intents<SayIntent> : UniqueTable<EmptyKey, Intents<SayIntent>> = say_intents
+    table Intents<ScriptError> : UniqueTable<EmptyKey, Intents<ScriptError>> = script_error_intents,
+    table Intents<CpuUsageIntent> : UniqueTable<EmptyKey, Intents<CpuUsageIntent>> = cpu_usage_intents,
+    table Intents<SayIntent> : UniqueTable<EmptyKey, Intents<SayIntent>> = say_intents,
+    table Intents<MemoryIntent> : UniqueTable<EmptyKey, Intents<MemoryIntent>> = memory_intents,
+    table Intents<MessagesIntent> : UniqueTable<EmptyKey, Intents<MessagesIntent>> = messages_intents
 );
 
 archetype!(
@@ -188,6 +217,7 @@ impl World {
             entity_handles: HandleTable::new(5_000_000),
             user: Default::default(),
         };
+        res.resources.rng.value = Some(WorldRng::from_entropy());
 
         // initialize the intent tables
         let botints = crate::intents::BotIntents::default();
@@ -249,6 +279,13 @@ impl World {
     pub fn list_users(&self) -> impl Iterator<Item = UserId> + '_ {
         self.user.user.iter().map(|(id, _)| id)
     }
+
+    /// Hash the persistent state of the world. Intents are not included.
+    ///
+    /// Use [StateHash::diff](StateHash::diff) to find which tables differ between two worlds.
+    pub fn state_hash(&self) -> StateHash {
+        state_hash::state_hash(self)
+    }
 }
 
 impl storage::DeferredDeleteById<EntityId> for World
@@ -271,6 +308,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::DeferredDeleteById;
     use test_env_log::test;
 
     #[test]
@@ -319,4 +357,78 @@ mod tests {
         let structures: Vec<_> = world.entities.iterby_structure().collect();
         serde_json::to_string_pretty(&structures).unwrap();
     }
+
+    #[test]
+    fn test_world_snapshot_restore() {
+        let mut world = World::new();
+
+        let mut bots = Vec::new();
+        for i in 0..4 {
+            let dead = world.insert_entity(); // produce gaps
+            let entity = world.insert_entity();
+
+            world.entities.bot.insert(entity);
+            world.entities.hp.insert(
+                entity,
+                HpComponent {
+                    hp: 10 * i,
+                    hp_max: 100,
+                },
+            );
+            world.entities.pos.insert(
+                entity,
+                PositionComponent(WorldPosition {
+                    room: Axial::new(42, 69),
+                    pos: Axial::new(16, i as i32),
+                }),
+            );
+            world.deferred_delete(dead);
+            bots.push((dead, entity));
+        }
+        world.post_process();
+
+        let payload = serde_json::to_string(&world).unwrap();
+        let mut restored: World = serde_json::from_str(payload.as_str()).unwrap();
+
+        assert_eq!(world.time(), restored.time());
+        assert_eq!(world.queen_tag(), restored.queen_tag());
+        for (dead, entity) in bots {
+            assert!(!restored.is_valid_entity(dead));
+            assert!(restored.is_valid_entity(entity));
+            assert!(restored.entities.bot.contains(&entity));
+            assert_eq!(
+                world.entities.hp.get(entity).map(|hp| hp.hp),
+                restored.entities.hp.get(entity).map(|hp| hp.hp),
+            );
+            assert_eq!(
+                world.entities.pos.get(entity),
+                restored.entities.pos.get(entity),
+            );
+        }
+        assert_eq!(world.state_hash(), restored.state_hash());
+        // new entities should reuse the same handles in both worlds
+        assert_eq!(world.insert_entity(), restored.insert_entity());
+    }
+
+    #[test]
+    fn test_state_hash_localises_mismatch() {
+        let mut world = World::new();
+        let entity = world.insert_entity();
+        world
+            .unsafe_view::<EntityId, HpComponent>()
+            .insert(entity, HpComponent { hp: 10, hp_max: 10 });
+
+        let before = world.state_hash();
+        assert_eq!(before, world.state_hash());
+
+        world
+            .unsafe_view::<EntityId, HpComponent>()
+            .get_mut(entity)
+            .unwrap()
+            .hp = 5;
+
+        let after = world.state_hash();
+        assert_ne!(before.hash, after.hash);
+        assert_eq!(before.diff(&after), vec!["entity_store::HpComponent"]);
+    }
 }
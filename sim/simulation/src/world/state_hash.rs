@@ -0,0 +1,126 @@
+//! Stable hashing of the world state, to compare worlds across runs and processes.
+//!
+//! Tables are hashed by their serialized form, which is in canonical order for every table
+//! type (entities by `EntityId`, positions by Morton order...).
+use std::{
+    hash::Hasher,
+    io::{self, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::World;
+
+/// FNV-1a hasher.
+///
+/// Unlike `DefaultHasher` the output is guaranteed to be the same across builds and platforms.
+#[derive(Debug, Clone, Copy)]
+pub struct StateHasher(u64);
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl Write for StateHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Hasher::write(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) fn hash_table<T: Serialize>(table: &T) -> u64 {
+    let mut hasher = StateHasher::default();
+    bincode::serialize_into(&mut hasher, table).expect("Failed to serialize table");
+    hasher.finish()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateHash {
+    /// Hash of the whole state
+    pub hash: u64,
+    /// Hashes of the individual tables, in canonical order
+    pub tables: Vec<(String, u64)>,
+}
+
+impl StateHash {
+    /// Names of the tables whose hashes differ
+    pub fn diff<'a>(&'a self, other: &'a StateHash) -> Vec<&'a str> {
+        let mut result = Vec::new();
+        let mut theirs = other.tables.iter().peekable();
+        for (name, hash) in self.tables.iter() {
+            match theirs.peek() {
+                Some((n, h)) if n == name => {
+                    if h != hash {
+                        result.push(name.as_str());
+                    }
+                    theirs.next();
+                }
+                _ => result.push(name.as_str()),
+            }
+        }
+        result.extend(theirs.map(|(n, _)| n.as_str()));
+        result
+    }
+}
+
+pub(super) fn state_hash(world: &World) -> StateHash {
+    let mut tables: Vec<(String, u64)> = Vec::with_capacity(64);
+    let mut push = |hashes: Vec<(&'static str, u64)>| {
+        tables.extend(
+            hashes
+                .into_iter()
+                .map(|(name, hash)| (name.to_owned(), hash)),
+        );
+    };
+
+    push(world.entities.table_hashes());
+    push(world.room.table_hashes());
+    push(world.user.table_hashes());
+    // compiled programs hold hash maps, so their serialized form is not stable. They are
+    // derived from the IR, so hashing the IR is enough
+    push(vec![(
+        "script_store::CaoIrComponent",
+        hash_table(&world.scripts.cao_ir),
+    )]);
+    push(world.config.table_hashes());
+    push(world.positions.table_hashes());
+    // intents are transient, only hash the persistent resources
+    push(vec![
+        ("resource_store::Time", hash_table(&world.resources.time)),
+        ("resource_store::WorldRng", hash_table(&world.resources.rng)),
+    ]);
+    push(vec![
+        ("entity_logs", hash_table(&world.entity_logs)),
+        ("entity_handles", hash_table(&world.entity_handles)),
+    ]);
+
+    let mut hasher = StateHasher::default();
+    for (name, hash) in tables.iter() {
+        Hasher::write(&mut hasher, name.as_bytes());
+        Hasher::write(&mut hasher, &hash.to_le_bytes());
+    }
+    StateHash {
+        hash: hasher.finish(),
+        tables,
+    }
+}
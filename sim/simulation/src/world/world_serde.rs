@@ -1,73 +1,119 @@
-use std::cell::RefCell;
+//! Snapshots of the World.
+//!
+//! Everything but the transient (per-tick) state is serialized, so a World can be restored from
+//! its latest snapshot.
+//!
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use serde::{
-    ser::{SerializeSeq, SerializeStruct},
-    Serialize, Serializer,
-};
+use super::*;
 
-use crate::prelude::*;
+#[derive(Serialize)]
+#[serde(rename = "CaoloWorld")]
+struct WorldRef<'a> {
+    entities: &'a entity_store::Archetype,
+    room: &'a room_store::Archetype,
+    user: &'a user_store::Archetype,
+    config: &'a config_store::Archetype,
+    resources: &'a resource_store::Archetype,
+    #[serde(serialize_with = "serialize_scripts")]
+    scripts: &'a script_store::Archetype,
+    entity_logs: &'a <LogEntry as Component<EntityTime>>::Table,
+    positions: &'a positions_store::Archetype,
+    entity_handles: &'a HandleTable,
+}
 
-/// serialize only once, will consume the iterator
-struct SerializeIter<T: Serialize, It: Iterator<Item = T>> {
-    it: RefCell<It>,
-    _m: std::marker::PhantomData<T>,
+#[derive(Deserialize)]
+#[serde(rename = "CaoloWorld")]
+struct WorldSnapshot {
+    entities: entity_store::Archetype,
+    room: room_store::Archetype,
+    user: user_store::Archetype,
+    config: config_store::Archetype,
+    resources: resource_store::Archetype,
+    #[serde(deserialize_with = "deserialize_scripts")]
+    scripts: script_store::Archetype,
+    entity_logs: <LogEntry as Component<EntityTime>>::Table,
+    positions: positions_store::Archetype,
+    entity_handles: HandleTable,
 }
 
-impl<T: Serialize, It: Iterator<Item = T>> SerializeIter<T, It> {
-    fn new(it: It) -> Self {
-        Self {
-            it: RefCell::new(it),
-            _m: Default::default(),
-        }
-    }
+/// The cao-lang IR can only be deserialized from self-describing formats, so scripts are
+/// embedded as json. This keeps snapshots compatible with formats like bincode.
+fn serialize_scripts<S>(
+    scripts: &&script_store::Archetype,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let payload = serde_json::to_string(scripts).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(payload.as_str())
+}
+
+fn deserialize_scripts<'de, D>(deserializer: D) -> Result<script_store::Archetype, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let payload = String::deserialize(deserializer)?;
+    serde_json::from_str(payload.as_str()).map_err(serde::de::Error::custom)
 }
 
-impl<T: Serialize, It: Iterator<Item = T>> Serialize for SerializeIter<T, It> {
+impl Serialize for World {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut it = self.it.borrow_mut();
-
-        let mut state = serializer.serialize_seq(it.size_hint().1)?;
-
-        for item in &mut *it {
-            state.serialize_element(&item)?;
+        WorldRef {
+            entities: &self.entities,
+            room: &self.room,
+            user: &self.user,
+            config: &self.config,
+            resources: &self.resources,
+            scripts: &self.scripts,
+            entity_logs: &self.entity_logs,
+            positions: &self.positions,
+            entity_handles: &self.entity_handles,
         }
-
-        state.end()
+        .serialize(serializer)
     }
 }
 
-impl Serialize for World {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+impl<'de> Deserialize<'de> for World {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        S: Serializer,
+        D: Deserializer<'de>,
     {
-        let mut state = serializer.serialize_struct("CaoloWorld", 9)?;
-        state.serialize_field("time", &self.time())?;
-        // ser entities
-        state.serialize_field("bots", &SerializeIter::new(self.entities.iterby_bot()))?;
-        state.serialize_field(
-            "structures",
-            &SerializeIter::new(self.entities.iterby_structure()),
-        )?;
-        state.serialize_field(
-            "resources",
-            &SerializeIter::new(self.entities.iterby_resource()),
-        )?;
-        // ser users
-        state.serialize_field("users", &SerializeIter::new(self.user.iterby_user()))?;
-        // ser scripts
-        state.serialize_field("scripts", &SerializeIter::new(self.scripts.iterby_cao_ir()))?;
-        // ser rooms
-        state.serialize_field("rooms", &SerializeIter::new(self.room.iterby_rooms()))?;
-        // ser config
-        state.serialize_field(
-            "room_properties",
-            &self.view::<ConfigKey, RoomProperties>().value,
-        )?;
-        state.serialize_field("game_config", &self.view::<ConfigKey, GameConfig>().value)?;
-        state.end()
+        let WorldSnapshot {
+            entities,
+            room,
+            user,
+            config,
+            resources,
+            scripts,
+            entity_logs,
+            positions,
+            entity_handles,
+        } = WorldSnapshot::deserialize(deserializer)?;
+
+        if config.game_config.value.is_none() {
+            return Err(serde::de::Error::missing_field("game_config"));
+        }
+
+        let mut world = World {
+            entities,
+            room,
+            user,
+            config,
+            resources,
+            scripts,
+            entity_logs,
+            positions,
+            entity_handles,
+            deferred_deletes: Default::default(),
+        };
+        // make sure the intent tables are initialized, even if the snapshot was taken before the
+        // first tick
+        crate::intents::move_into_storage(&mut world, vec![]);
+        Ok(world)
     }
 }
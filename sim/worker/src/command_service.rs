@@ -1,11 +1,14 @@
 use crate::input::structures;
+use crate::replay::{Command, Recorder};
 use crate::{input::rooms, protos::cao_commands};
+use prost::Message;
 use tonic::{Request, Response, Status};
 use tracing::info;
 
 #[derive(Clone)]
 pub struct CommandService {
     world: crate::WorldContainer,
+    recorder: Recorder,
 }
 
 impl std::fmt::Debug for CommandService {
@@ -15,8 +18,8 @@ impl std::fmt::Debug for CommandService {
 }
 
 impl CommandService {
-    pub fn new(world: crate::WorldContainer) -> Self {
-        Self { world }
+    pub fn new(world: crate::WorldContainer, recorder: Recorder) -> Self {
+        Self { world, recorder }
     }
 }
 
@@ -28,6 +31,8 @@ impl cao_commands::command_server::Command for CommandService {
     ) -> Result<Response<cao_commands::CommandResult>, Status> {
         info!("Placing structure");
         let mut w = self.world.write().await;
+        self.recorder
+            .record_command(Command::PlaceStructure(request.get_ref().encode_to_vec()));
         structures::place_structure(&mut w, request.get_ref())
             .map(|_: ()| Response::new(cao_commands::CommandResult {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
@@ -39,6 +44,8 @@ impl cao_commands::command_server::Command for CommandService {
     ) -> Result<tonic::Response<cao_commands::CommandResult>, tonic::Status> {
         info!("Taking room");
         let mut w = self.world.write().await;
+        self.recorder
+            .record_command(Command::TakeRoom(request.get_ref().encode_to_vec()));
         rooms::take_room(&mut w, request.get_ref())
             .map(|_: ()| Response::new(cao_commands::CommandResult {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
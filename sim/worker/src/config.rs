@@ -1,5 +1,5 @@
 use serde::Serialize;
-use std::env;
+use std::{env, path::PathBuf};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +10,17 @@ pub struct Config {
     pub target_tick_ms: u64,
     /// Number of previous world states to hold on to, for slow clients
     pub world_buff_size: u64,
+    /// Save a world snapshot every `snapshot_interval` ticks. 0 disables snapshots
+    pub snapshot_interval: u64,
+    /// Number of snapshots to keep
+    pub snapshot_generations: usize,
+    pub snapshot_dir: PathBuf,
+    /// Snapshot file, or directory of snapshots, to restore the world from on startup
+    pub restore_path: Option<PathBuf>,
+    /// Record intents and commands to this file, for replays
+    pub record_path: Option<PathBuf>,
+    /// If set, replay this log on top of the restored world, then exit
+    pub replay_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -20,6 +31,12 @@ impl Default for Config {
             world_radius: 8,
             target_tick_ms: 200,
             world_buff_size: 1,
+            snapshot_interval: 0,
+            snapshot_generations: 2,
+            snapshot_dir: PathBuf::from("snapshots"),
+            restore_path: None,
+            record_path: None,
+            replay_path: None,
         }
     }
 }
@@ -50,6 +67,40 @@ impl Config {
             world_buff_size: std::env::var("CAO_WORLD_BUFFER")
                 .map(|i| i.parse::<u64>().unwrap())
                 .unwrap_or(1),
+            snapshot_interval: std::env::var("CAO_SNAPSHOT_INTERVAL")
+                .map(|i| {
+                    i.parse::<u64>()
+                        .expect("expected snapshot interval to be an integer")
+                })
+                .unwrap_or(0),
+            snapshot_generations: std::env::var("CAO_SNAPSHOT_GENERATIONS")
+                .map(|i| {
+                    i.parse::<usize>()
+                        .expect("expected snapshot generations to be an integer")
+                })
+                .unwrap_or(2),
+            snapshot_dir: std::env::var("CAO_SNAPSHOT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("snapshots")),
+            restore_path: path_arg("--restore-from")
+                .or_else(|| std::env::var("CAO_RESTORE_PATH").ok().map(PathBuf::from)),
+            record_path: path_arg("--record-to")
+                .or_else(|| std::env::var("CAO_RECORD_PATH").ok().map(PathBuf::from)),
+            replay_path: path_arg("--replay"),
+        }
+    }
+}
+
+/// Parse a `<name> <path>` or `<name>=<path>` command line argument
+fn path_arg(name: &str) -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix(name).and_then(|a| a.strip_prefix('=')) {
+            return Some(PathBuf::from(path));
         }
     }
+    None
 }
@@ -7,61 +7,61 @@ use caolo_sim::executor::SimpleExecutor;
 use tokio::sync::broadcast::Sender;
 use tracing::{debug, info, warn};
 
-use crate::{world_service, WorldContainer};
+use crate::{
+    metrics::{Metrics, TickMetrics},
+    replay::Recorder,
+    snapshot::Snapshotter,
+    world_service, WorldContainer,
+};
 
 pub async fn game_loop(
     world: WorldContainer,
     mut executor: SimpleExecutor,
     outpayload: Arc<Sender<Arc<world_service::Payload>>>,
     tick_latency: Duration,
+    snapshots: Option<(u64, Snapshotter)>,
+    recorder: Recorder,
+    metrics: Metrics,
 ) {
     let mut lag = Duration::new(0, 0);
     loop {
         let start = Instant::now();
 
-        #[cfg(save_world)]
-        {
-            // save the latest world state on a background thread
-            // TODO use two files and double-buffer based on time()?
-            // so if save fails we'll still have the one-before the last save
-            let world = world.clone();
-            tokio::spawn(async move {
-                let start = Instant::now();
-                let world_guard = world.read().await;
-                let mut f = std::fs::OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .open("latest_world.bin")
-                    .unwrap();
-                bincode::serialize_into(&mut f, &*world_guard).unwrap();
-                drop(world_guard);
-
-                let end = Instant::now();
-
-                info!("Saved current world state in {:?}", end - start);
-            });
+        if let Some((interval, snapshotter)) = snapshots.as_ref() {
+            let time = world.read().await.time();
+            if time % interval == 0 {
+                // serialization happens on a background task, under a read guard
+                snapshotter.spawn_snapshot(Arc::clone(&world));
+            }
         }
 
         let world_guard = world.read().await;
         let sp = tracing::error_span!("game-loop", tick = world_guard.time());
         let _e = sp.enter();
 
-        let intents = executor.forward_bots(&world_guard).await.unwrap();
+        let run_result = executor.forward_bots(&world_guard).await.unwrap();
+        let intents = run_result.intents;
         drop(world_guard); // free the read guard
 
         // NOTE: commands may be executed between `forward_bots` and `apply_intents`
         // allow this for now, but may be worth revisiting
 
         let mut world_guard = world.write().await;
-        executor
+        let time = world_guard.time();
+        let recorded_intents = recorder.is_enabled().then(|| intents.clone());
+        let system_durations = executor
             .apply_intents(&mut world_guard, intents)
             .await
             .unwrap();
+        if let Some(intents) = recorded_intents {
+            recorder.record_tick(time, intents, &world_guard);
+        }
         drop(world_guard); // free the write guard
 
         let world_guard = world.read().await;
         let mut pl = world_service::Payload::default();
         pl.update(&world_guard);
+        let entity_counts = TickMetrics::count_entities(&world_guard);
         drop(world_guard); // free the read guard
 
         if outpayload.receiver_count() > 0 {
@@ -91,6 +91,17 @@ pub async fn game_loop(
             "Tick done in {:.2?}. Current lag: {:.2?}",
             tick_duration, lag
         );
+        metrics.record_tick(TickMetrics {
+            time,
+            tick_duration,
+            lag,
+            system_durations,
+            scripts_ran: run_result.num_scripts_ran,
+            scripts_errored: run_result.num_scripts_errored,
+            scripts_skipped: run_result.num_scripts_skipped,
+            entity_counts,
+            subscribers: outpayload.receiver_count(),
+        });
 
         tokio::time::sleep(sleep_duration).await;
     }
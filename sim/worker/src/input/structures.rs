@@ -107,6 +107,18 @@ pub fn place_structure(
                 entity_id, owner_id, position, storage,
             );
         }
+        StructureType::Container | StructureType::Tower => {
+            let ty = match ty {
+                StructureType::Container => caolo_sim::components::StructureType::Container,
+                _ => caolo_sim::components::StructureType::Tower,
+            };
+
+            entity_id = storage.insert_entity();
+
+            caolo_sim::entity_archetypes::init_structure(
+                entity_id, ty, owner_id, position, storage,
+            );
+        }
     }
 
     Ok(())
@@ -1,7 +1,10 @@
 mod config;
 mod game_loop;
 mod input;
+mod metrics;
 mod protos;
+mod replay;
+mod snapshot;
 
 mod command_service;
 mod health_service;
@@ -16,7 +19,7 @@ use crate::protos::cao_users::users_server::UsersServer;
 use crate::protos::cao_world::world_server::WorldServer;
 use caolo_sim::executor::{GameConfig, SimpleExecutor};
 use std::{env, sync::Arc, time::Duration};
-use tracing::{info, Instrument};
+use tracing::{error, info, Instrument};
 use uuid::Uuid;
 
 use opentelemetry::global;
@@ -70,25 +73,81 @@ async fn main() {
         script_chunk_size, tick_latency
     );
 
-    let tag = env::var("CAO_QUEEN_TAG").unwrap_or_else(|_| Uuid::new_v4().to_string());
-    let world_span = tracing::error_span!("world-service", queen_tag = tag.as_str());
-    let game_loop_span = tracing::error_span!("game-loop", queen_tag = tag.as_str());
+    let tag = env::var("CAO_QUEEN_TAG").ok();
 
-    info!("Creating cao executor with tag {}", tag);
     let mut executor = SimpleExecutor;
-    info!("Init storage");
-    let mut world = executor
-        .initialize(GameConfig {
-            world_radius: config.world_radius,
-            room_radius: config.room_radius,
-            queen_tag: tag.clone(),
-            ..Default::default()
-        })
-        .await;
-
-    info!("Starting with {} actors", config.n_actors);
+    let mut world = match config.restore_path.as_ref() {
+        Some(path) => {
+            info!("Restoring world from {:?}", path);
+            let mut world = snapshot::load_latest(path).expect("Failed to restore the world");
+            if let Some(tag) = tag {
+                world
+                    .unsafe_view::<caolo_sim::indices::ConfigKey, GameConfig>()
+                    .unwrap_mut()
+                    .queen_tag = tag;
+            }
+            world
+        }
+        None => {
+            let tag = tag.unwrap_or_else(|| Uuid::new_v4().to_string());
+            info!("Init storage");
+            let mut world = executor
+                .initialize(GameConfig {
+                    world_radius: config.world_radius,
+                    room_radius: config.room_radius,
+                    queen_tag: tag,
+                    ..Default::default()
+                })
+                .await;
+
+            info!("Starting with {} actors", config.n_actors);
+
+            caolo_sim::init::init_world_entities(&mut world, config.n_actors as usize);
+            world
+        }
+    };
+
+    if let Some(log) = config.replay_path.as_ref() {
+        assert!(
+            config.restore_path.is_some(),
+            "Replays need a snapshot to start from, set --restore-from"
+        );
+        info!("Replaying {:?}", log);
+        let log = std::fs::File::open(log).expect("Failed to open replay log");
+        let report = replay::replay(&mut world, log)
+            .await
+            .expect("Failed to replay log");
+        match report.first_divergence {
+            Some(divergence) => {
+                error!(
+                    "Replay diverged after {} ticks: {:?}",
+                    report.ticks_replayed, divergence
+                );
+                std::process::exit(1);
+            }
+            None => info!(
+                "Replayed {} ticks without divergence",
+                report.ticks_replayed
+            ),
+        }
+        return;
+    }
 
-    caolo_sim::init::init_world_entities(&mut world, config.n_actors as usize);
+    let recorder = match config.record_path.as_ref() {
+        Some(path) => {
+            info!("Recording intents and commands to {:?}", path);
+            replay::Recorder::create(path).expect("Failed to create the record log")
+        }
+        None => replay::Recorder::disabled(),
+    };
+
+    let tag = world
+        .queen_tag()
+        .expect("world has no game config")
+        .to_owned();
+    info!("Running cao executor with tag {}", tag);
+    let world_span = tracing::error_span!("world-service", queen_tag = tag.as_str());
+    let game_loop_span = tracing::error_span!("game-loop", queen_tag = tag.as_str());
 
     let addr = env::var("CAO_SERVICE_ADDR")
         .ok()
@@ -97,6 +156,13 @@ async fn main() {
 
     info!("Starting the game loop. Starting the service on {:?}", addr);
 
+    let metrics_addr = env::var("CAO_METRICS_ADDR")
+        .ok()
+        .map(|x| x.parse().expect("failed to parse cao metrics address"))
+        .unwrap_or_else(|| "[::1]:9090".parse().unwrap());
+    let metrics = metrics::Metrics::default();
+    let metrics_server = metrics::serve(metrics_addr, metrics.clone());
+
     let (outtx, _) = tokio::sync::broadcast::channel(config.world_buff_size as usize);
     let outpayload = Arc::new(outtx);
 
@@ -128,10 +194,10 @@ async fn main() {
     let server = tonic::transport::Server::builder()
         .trace_fn(move |_| tracing::error_span!("service", queen_tag = tag.as_str()))
         .add_service(CommandServer::new(
-            crate::command_service::CommandService::new(Arc::clone(&world)),
+            crate::command_service::CommandService::new(Arc::clone(&world), recorder.clone()),
         ))
         .add_service(ScriptingServer::new(
-            crate::scripting_service::ScriptingService::new(Arc::clone(&world)),
+            crate::scripting_service::ScriptingService::new(Arc::clone(&world), recorder.clone()),
         ))
         .add_service(WorldServer::new(crate::world_service::WorldService::new(
             Arc::clone(&outpayload),
@@ -143,16 +209,37 @@ async fn main() {
         .add_service(HealthServer::new(health_service::HealthService {}))
         .add_service(UsersServer::new(crate::users_service::UsersService::new(
             Arc::clone(&world),
+            recorder.clone(),
         )))
         .serve(addr);
 
-    let game_loop =
-        game_loop::game_loop(world, executor, outpayload, tick_latency).instrument(game_loop_span);
+    let snapshots = (config.snapshot_interval > 0).then(|| {
+        (
+            config.snapshot_interval,
+            snapshot::Snapshotter::new(
+                config.snapshot_dir.clone(),
+                config.snapshot_generations,
+                recorder.clone(),
+            ),
+        )
+    });
+
+    let game_loop = game_loop::game_loop(
+        world,
+        executor,
+        outpayload,
+        tick_latency,
+        snapshots,
+        recorder,
+        metrics,
+    )
+    .instrument(game_loop_span);
 
     info!(
         "Initialization done in {:?}",
         std::time::Instant::now() - now
     );
-    let (a, _) = futures::join!(server, game_loop);
+    let (a, b, _) = futures::join!(server, metrics_server, game_loop);
     a.unwrap();
+    b.unwrap();
 }
@@ -0,0 +1,255 @@
+//! Per-tick metrics, served in the Prometheus text exposition format on `/metrics`
+use std::{
+    convert::Infallible,
+    fmt::Write,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use caolo_sim::prelude::*;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, StatusCode,
+};
+use tracing::info;
+
+/// Metrics of a single tick
+#[derive(Debug, Clone, Default)]
+pub struct TickMetrics {
+    pub time: u64,
+    pub tick_duration: Duration,
+    pub lag: Duration,
+    pub system_durations: Vec<(&'static str, Duration)>,
+    pub scripts_ran: u64,
+    pub scripts_errored: u64,
+    pub scripts_skipped: u64,
+    pub entity_counts: Vec<(&'static str, usize)>,
+    pub subscribers: usize,
+}
+
+impl TickMetrics {
+    /// Count the entities of each archetype in `world`
+    pub fn count_entities(world: &World) -> Vec<(&'static str, usize)> {
+        vec![
+            ("bot", world.view::<EntityId, Bot>().iter().count()),
+            (
+                "structure",
+                world.view::<EntityId, Structure>().iter().count(),
+            ),
+            (
+                "resource",
+                world.view::<EntityId, ResourceComponent>().iter().count(),
+            ),
+            ("user", world.list_users().count()),
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsInner {
+    ticks_total: u64,
+    scripts_ran_total: u64,
+    scripts_errored_total: u64,
+    last_tick: TickMetrics,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<RwLock<MetricsInner>>,
+}
+
+impl Metrics {
+    pub fn record_tick(&self, tick: TickMetrics) {
+        let mut inner = self.inner.write().expect("Failed to acquire metrics lock");
+        inner.ticks_total += 1;
+        inner.scripts_ran_total += tick.scripts_ran;
+        inner.scripts_errored_total += tick.scripts_errored;
+        inner.last_tick = tick;
+    }
+
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let inner = self.inner.read().expect("Failed to acquire metrics lock");
+        let tick = &inner.last_tick;
+        let mut out = String::with_capacity(4096);
+
+        let mut metric = |name: &str, ty: &str, help: &str, values: &[(String, f64)]| {
+            writeln!(out, "# HELP {} {}", name, help).unwrap();
+            writeln!(out, "# TYPE {} {}", name, ty).unwrap();
+            for (labels, value) in values {
+                writeln!(out, "{}{} {}", name, labels, value).unwrap();
+            }
+        };
+        let single = |value: f64| [(String::new(), value)];
+
+        metric(
+            "caolo_ticks_total",
+            "counter",
+            "Number of ticks executed since startup",
+            &single(inner.ticks_total as f64),
+        );
+        metric(
+            "caolo_world_time",
+            "gauge",
+            "Time of the world at the start of the last tick",
+            &single(tick.time as f64),
+        );
+        metric(
+            "caolo_tick_duration_seconds",
+            "gauge",
+            "Duration of the last tick",
+            &single(tick.tick_duration.as_secs_f64()),
+        );
+        metric(
+            "caolo_tick_lag_seconds",
+            "gauge",
+            "Time the game loop is behind the target tick latency",
+            &single(tick.lag.as_secs_f64()),
+        );
+        metric(
+            "caolo_system_duration_seconds",
+            "gauge",
+            "Duration of each system in the last tick",
+            &tick
+                .system_durations
+                .iter()
+                .map(|(name, d)| (format!("{{system=\"{}\"}}", name), d.as_secs_f64()))
+                .collect::<Vec<_>>(),
+        );
+        metric(
+            "caolo_scripts_ran",
+            "gauge",
+            "Number of scripts executed in the last tick",
+            &single(tick.scripts_ran as f64),
+        );
+        metric(
+            "caolo_scripts_errored",
+            "gauge",
+            "Number of scripts that failed in the last tick",
+            &single(tick.scripts_errored as f64),
+        );
+        metric(
+            "caolo_scripts_skipped",
+            "gauge",
+            "Number of scripts skipped in the last tick because their owner ran out of cpu",
+            &single(tick.scripts_skipped as f64),
+        );
+        metric(
+            "caolo_scripts_ran_total",
+            "counter",
+            "Number of scripts executed since startup",
+            &single(inner.scripts_ran_total as f64),
+        );
+        metric(
+            "caolo_scripts_errored_total",
+            "counter",
+            "Number of scripts that failed since startup",
+            &single(inner.scripts_errored_total as f64),
+        );
+        metric(
+            "caolo_entities",
+            "gauge",
+            "Number of entities by archetype",
+            &tick
+                .entity_counts
+                .iter()
+                .map(|(kind, n)| (format!("{{kind=\"{}\"}}", kind), *n as f64))
+                .collect::<Vec<_>>(),
+        );
+        metric(
+            "caolo_world_subscribers",
+            "gauge",
+            "Number of clients subscribed to world updates",
+            &single(tick.subscribers as f64),
+        );
+
+        out
+    }
+}
+
+pub async fn serve(addr: SocketAddr, metrics: Metrics) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_conn| {
+        let metrics = metrics.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let metrics = metrics.clone();
+                async move { Ok::<_, Infallible>(handle(req, &metrics)) }
+            }))
+        }
+    });
+    info!("Serving metrics on {:?}", addr);
+    hyper::Server::bind(&addr).serve(make_service).await
+}
+
+fn handle(req: Request<Body>, metrics: &Metrics) -> Response<Body> {
+    if req.uri().path() != "/metrics" {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap();
+    }
+    Response::builder()
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(Body::from(metrics.render()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_tick(TickMetrics {
+            time: 42,
+            tick_duration: Duration::from_millis(150),
+            scripts_ran: 10,
+            scripts_errored: 3,
+            subscribers: 2,
+            ..Default::default()
+        });
+        metrics.record_tick(TickMetrics {
+            scripts_ran: 5,
+            ..Default::default()
+        });
+
+        let text = metrics.render();
+
+        assert!(text.contains("caolo_ticks_total 2\n"));
+        assert!(text.contains("caolo_scripts_ran 5\n"));
+        assert!(text.contains("caolo_scripts_ran_total 15\n"));
+        assert!(text.contains("caolo_scripts_errored_total 3\n"));
+        assert!(text.contains("# TYPE caolo_tick_duration_seconds gauge\n"));
+    }
+
+    #[test]
+    fn test_render_labels() {
+        let metrics = Metrics::default();
+        metrics.record_tick(TickMetrics {
+            system_durations: vec![("attack_system_update", Duration::from_millis(2))],
+            entity_counts: vec![("bot", 7)],
+            ..Default::default()
+        });
+
+        let text = metrics.render();
+
+        assert!(
+            text.contains("caolo_system_duration_seconds{system=\"attack_system_update\"} 0.002\n")
+        );
+        assert!(text.contains("caolo_entities{kind=\"bot\"} 7\n"));
+    }
+
+    #[test]
+    fn test_handle_only_serves_metrics_path() {
+        let metrics = Metrics::default();
+        let get = |path: &str| Request::get(path).body(Body::empty()).unwrap();
+
+        let res = handle(get("/metrics"), &metrics);
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = handle(get("/"), &metrics);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
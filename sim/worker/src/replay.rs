@@ -0,0 +1,308 @@
+//! Record the inputs of the simulation and replay them on top of a snapshot.
+//!
+//! The log is a stream of bincode encoded [Record](Record)s. Each tick's intents are recorded
+//! together with the hash of the world after applying them, so a replay can report the first
+//! tick where it diverges from the recorded run.
+use std::{
+    fs,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use caolo_sim::{
+    executor::{BotIntents, SimpleExecutor},
+    prelude::World,
+    world::StateHash,
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    input::{rooms, script_update, structures, users},
+    protos::{cao_commands, cao_script, cao_users},
+};
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Log io failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to (de)serialize a record: {0}")]
+    Serialization(#[from] bincode::Error),
+    #[error("Failed to decode command: {0}")]
+    Decode(#[from] prost::DecodeError),
+}
+
+/// Commands that mutate the world between ticks, stored as their encoded protobuf messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Command {
+    PlaceStructure(Vec<u8>),
+    TakeRoom(Vec<u8>),
+    UpdateEntityScript(Vec<u8>),
+    UpdateScript(Vec<u8>),
+    SetDefaultScript(Vec<u8>),
+    RegisterUser(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Record {
+    Command(Command),
+    Tick {
+        time: u64,
+        intents: Vec<BotIntents>,
+        /// Hash of the world after the intents were applied
+        world_hash: StateHash,
+    },
+    /// A snapshot of the world was taken at `time`. Commands recorded before this were
+    /// included in the snapshot
+    Snapshot {
+        time: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub time: u64,
+    pub expected_hash: u64,
+    pub actual_hash: u64,
+    /// Tables that differ from the recording
+    pub tables: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplayReport {
+    pub ticks_replayed: u64,
+    pub commands_replayed: u64,
+    pub first_divergence: Option<Divergence>,
+}
+
+/// Writes records to the log. Recording is a no-op if the recorder is disabled.
+///
+/// Callers should record while holding the world's write guard, so the order of the
+/// records matches the order their effects were applied in.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    out: Option<Arc<Mutex<BufWriter<fs::File>>>>,
+}
+
+impl Recorder {
+    pub fn disabled() -> Self {
+        Self { out: None }
+    }
+
+    /// Truncates the file at `path`, if exists
+    pub fn create(path: &Path) -> Result<Self, ReplayError> {
+        let f = fs::File::create(path)?;
+        Ok(Self {
+            out: Some(Arc::new(Mutex::new(BufWriter::new(f)))),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    pub fn record_command(&self, command: Command) {
+        self.record(&Record::Command(command), false);
+    }
+
+    pub fn record_snapshot(&self, time: u64) {
+        self.record(&Record::Snapshot { time }, true);
+    }
+
+    pub fn record_tick(&self, time: u64, intents: Vec<BotIntents>, world: &World) {
+        if !self.is_enabled() {
+            return;
+        }
+        let record = Record::Tick {
+            time,
+            intents,
+            world_hash: world.state_hash(),
+        };
+        self.record(&record, true);
+    }
+
+    fn record(&self, record: &Record, flush: bool) {
+        let out = match self.out.as_ref() {
+            Some(out) => out,
+            None => return,
+        };
+        let mut out = out.lock().expect("Failed to acquire recorder lock");
+        let res = bincode::serialize_into(&mut *out, record)
+            .map_err(ReplayError::from)
+            .and_then(|_| {
+                if flush {
+                    out.flush()?;
+                }
+                Ok(())
+            });
+        if let Err(err) = res {
+            error!("Failed to record {:?}: {}", record, err);
+        }
+    }
+}
+
+impl Command {
+    pub fn apply(&self, world: &mut World) -> Result<(), ReplayError> {
+        // errors of the commands themselves are not replay errors, the live run
+        // returned them to the client the same way
+        let res = match self {
+            Command::PlaceStructure(payload) => {
+                let msg = cao_commands::PlaceStructureCommand::decode(payload.as_slice())?;
+                structures::place_structure(world, &msg).map_err(|err| err.to_string())
+            }
+            Command::TakeRoom(payload) => {
+                let msg = cao_commands::TakeRoomCommand::decode(payload.as_slice())?;
+                rooms::take_room(world, &msg).map_err(|err| err.to_string())
+            }
+            Command::UpdateEntityScript(payload) => {
+                let msg = cao_script::UpdateEntityScriptCommand::decode(payload.as_slice())?;
+                script_update::update_entity_script(world, &msg).map_err(|err| err.to_string())
+            }
+            Command::UpdateScript(payload) => {
+                let msg = cao_script::UpdateScriptCommand::decode(payload.as_slice())?;
+                script_update::update_program(world, &msg).map_err(|err| err.to_string())
+            }
+            Command::SetDefaultScript(payload) => {
+                let msg = cao_script::SetDefaultScriptCommand::decode(payload.as_slice())?;
+                script_update::set_default_script(world, &msg).map_err(|err| err.to_string())
+            }
+            Command::RegisterUser(payload) => {
+                let msg = cao_users::RegisterUserMsg::decode(payload.as_slice())?;
+                users::register_user(world, &msg).map_err(|err| err.to_string())
+            }
+        };
+        if let Err(err) = res {
+            debug!("Replayed command failed: {}", err);
+        }
+        Ok(())
+    }
+}
+
+/// Replay the records in `log` on top of `world`.
+///
+/// Records before the current time of the world are skipped, so `world` can be any snapshot
+/// taken during the recorded run. Stops at the first divergence.
+pub async fn replay(world: &mut World, log: impl Read) -> Result<ReplayReport, ReplayError> {
+    let mut log = io::BufReader::new(log);
+    let mut executor = SimpleExecutor;
+    let mut report = ReplayReport::default();
+    // commands recorded before the snapshot marker of `world` have been applied to it already
+    let mut pending_commands = Vec::new();
+    let mut started = false;
+    loop {
+        let record: Record = match bincode::deserialize_from(&mut log) {
+            Ok(record) => record,
+            Err(err) => match *err {
+                bincode::ErrorKind::Io(ref io_err)
+                    if io_err.kind() == io::ErrorKind::UnexpectedEof =>
+                {
+                    break;
+                }
+                _ => return Err(err.into()),
+            },
+        };
+        match record {
+            Record::Command(command) => pending_commands.push(command),
+            Record::Snapshot { time } => {
+                if !started && time == world.time() {
+                    pending_commands.clear();
+                }
+            }
+            Record::Tick {
+                time,
+                intents,
+                world_hash: expected_hash,
+            } => {
+                if time < world.time() {
+                    pending_commands.clear();
+                    continue;
+                }
+                if !started && time > world.time() {
+                    warn!(
+                        "Log starts at tick {}, but the world is at tick {}",
+                        time,
+                        world.time()
+                    );
+                }
+                started = true;
+                for command in pending_commands.drain(..) {
+                    command.apply(world)?;
+                    report.commands_replayed += 1;
+                }
+                executor
+                    .apply_intents(world, intents)
+                    .await
+                    .expect("Failed to apply intents");
+                report.ticks_replayed += 1;
+
+                let actual_hash = world.state_hash();
+                if actual_hash.hash != expected_hash.hash {
+                    let divergence = Divergence {
+                        time,
+                        expected_hash: expected_hash.hash,
+                        actual_hash: actual_hash.hash,
+                        tables: expected_hash
+                            .diff(&actual_hash)
+                            .into_iter()
+                            .map(|t| t.to_owned())
+                            .collect(),
+                    };
+                    warn!("Replay diverged {:?}", divergence);
+                    report.first_divergence = Some(divergence);
+                    break;
+                }
+            }
+        }
+    }
+    info!("Replay done {:?}", report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use caolo_sim::executor::GameConfig;
+
+    #[test]
+    fn test_replay_matches_recording() {
+        futures_lite::future::block_on(async {
+            let mut exc = SimpleExecutor;
+            let mut world = exc
+                .initialize(GameConfig {
+                    world_radius: 1,
+                    room_radius: 8,
+                    seed: Some(0x1337),
+                    ..Default::default()
+                })
+                .await;
+            caolo_sim::init::init_world_entities(&mut world, 2);
+            let snapshot = bincode::serialize(&world).unwrap();
+
+            let path = std::env::temp_dir().join(format!("caolo-replay-{}", uuid::Uuid::new_v4()));
+            let recorder = Recorder::create(&path).unwrap();
+            for _ in 0..4 {
+                let time = world.time();
+                let intents = exc.forward_bots(&world).await.unwrap().intents;
+                exc.apply_intents(&mut world, intents.clone())
+                    .await
+                    .unwrap();
+                recorder.record_tick(time, intents, &world);
+            }
+            drop(recorder);
+
+            let mut restored: World = bincode::deserialize(&snapshot).unwrap();
+            let report = replay(&mut restored, fs::File::open(&path).unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(report.ticks_replayed, 4);
+            assert!(report.first_divergence.is_none(), "{:?}", report);
+            assert_eq!(world.state_hash(), restored.state_hash());
+
+            fs::remove_file(&path).unwrap();
+        });
+    }
+}
@@ -1,7 +1,12 @@
 use crate::input::script_update;
 use crate::protos::cao_common;
 use crate::protos::cao_script;
-use caolo_sim::{components::CaoIrComponent, indices::ScriptId};
+use crate::replay::{Command, Recorder};
+use caolo_sim::{
+    components::{CaoIrComponent, OwnedEntity, ScriptErrors},
+    indices::{EntityId, ScriptId, UserId},
+};
+use prost::Message;
 use std::convert::TryInto;
 use tonic::{Response, Status};
 use tracing::debug;
@@ -9,6 +14,7 @@ use tracing::debug;
 #[derive(Clone)]
 pub struct ScriptingService {
     world: crate::WorldContainer,
+    recorder: Recorder,
 }
 
 impl std::fmt::Debug for ScriptingService {
@@ -18,11 +24,31 @@ impl std::fmt::Debug for ScriptingService {
 }
 
 impl ScriptingService {
-    pub fn new(world: crate::WorldContainer) -> Self {
-        Self { world }
+    pub fn new(world: crate::WorldContainer, recorder: Recorder) -> Self {
+        Self { world, recorder }
     }
 }
 
+fn script_error_list(errors: Option<&ScriptErrors>) -> cao_script::ScriptErrorList {
+    let errors = errors
+        .map(|errors| {
+            errors
+                .0
+                .iter()
+                .map(|err| cao_script::ScriptError {
+                    time: err.time as i64,
+                    entity_id: err.entity_id.into(),
+                    script_id: Some(cao_common::Uuid {
+                        data: err.script_id.0.as_bytes().to_vec(),
+                    }),
+                    error: err.error.clone(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    cao_script::ScriptErrorList { errors }
+}
+
 #[tonic::async_trait]
 impl cao_script::scripting_server::Scripting for ScriptingService {
     async fn list_scripts(
@@ -80,6 +106,9 @@ impl cao_script::scripting_server::Scripting for ScriptingService {
         request: tonic::Request<cao_script::UpdateEntityScriptCommand>,
     ) -> Result<tonic::Response<cao_script::CommandResult>, tonic::Status> {
         let mut w = self.world.write().await;
+        self.recorder.record_command(Command::UpdateEntityScript(
+            request.get_ref().encode_to_vec(),
+        ));
         script_update::update_entity_script(&mut *w, request.get_ref())
             .map(|_: ()| Response::new(cao_script::CommandResult {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
@@ -90,6 +119,8 @@ impl cao_script::scripting_server::Scripting for ScriptingService {
         request: tonic::Request<cao_script::UpdateScriptCommand>,
     ) -> Result<tonic::Response<cao_script::CommandResult>, tonic::Status> {
         let mut w = self.world.write().await;
+        self.recorder
+            .record_command(Command::UpdateScript(request.get_ref().encode_to_vec()));
         script_update::update_program(&mut *w, request.get_ref())
             .map(|_: ()| Response::new(cao_script::CommandResult {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
@@ -100,6 +131,8 @@ impl cao_script::scripting_server::Scripting for ScriptingService {
         request: tonic::Request<cao_script::SetDefaultScriptCommand>,
     ) -> Result<tonic::Response<cao_script::CommandResult>, tonic::Status> {
         let mut w = self.world.write().await;
+        self.recorder
+            .record_command(Command::SetDefaultScript(request.get_ref().encode_to_vec()));
         script_update::set_default_script(&mut *w, request.get_ref())
             .map(|_: ()| Response::new(cao_script::CommandResult {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
@@ -158,4 +191,47 @@ impl cao_script::scripting_server::Scripting for ScriptingService {
 
         Ok(tonic::Response::new(schema))
     }
+
+    async fn get_entity_script_errors(
+        &self,
+        request: tonic::Request<cao_script::EntityScriptErrorsQuery>,
+    ) -> Result<tonic::Response<cao_script::ScriptErrorList>, tonic::Status> {
+        let msg = request.get_ref();
+        let user_id = msg
+            .user_id
+            .as_ref()
+            .ok_or_else(|| tonic::Status::invalid_argument("Missing user id"))?;
+        let user_id = uuid::Uuid::from_slice(&user_id.data).map_err(|err| {
+            debug!("Failed to parse uuid {:?}", err);
+            tonic::Status::invalid_argument("User id is malformed, expected UUID")
+        })?;
+        let id = EntityId::from(msg.entity_id);
+        let w = self.world.read().await;
+        if !w.is_valid_entity(id) {
+            return Err(tonic::Status::not_found("Entity id is invalid"));
+        }
+        match w.view::<EntityId, OwnedEntity>().get(id) {
+            Some(owner) if owner.owner_id == UserId(user_id) => {}
+            _ => {
+                return Err(tonic::Status::permission_denied(
+                    "Entity is not owned by the user",
+                ))
+            }
+        }
+        let payload = script_error_list(w.view::<EntityId, ScriptErrors>().get(id));
+        Ok(tonic::Response::new(payload))
+    }
+
+    async fn get_user_script_errors(
+        &self,
+        request: tonic::Request<cao_common::Uuid>,
+    ) -> Result<tonic::Response<cao_script::ScriptErrorList>, tonic::Status> {
+        let user_id = uuid::Uuid::from_slice(&request.get_ref().data).map_err(|err| {
+            debug!("Failed to parse uuid {:?}", err);
+            tonic::Status::invalid_argument("User id is malformed, expected UUID")
+        })?;
+        let w = self.world.read().await;
+        let payload = script_error_list(w.view::<UserId, ScriptErrors>().get(UserId(user_id)));
+        Ok(tonic::Response::new(payload))
+    }
 }
@@ -0,0 +1,231 @@
+//! Crash-safe world snapshots.
+//!
+//! Snapshots are written to a temporary file first, then atomically renamed to
+//! `world_<tick>.bin`, so a crash mid-write never clobbers an existing snapshot.
+//! The last `generations` snapshots are kept, so if the newest one turns out to be
+//! corrupt we can still fall back to an older one.
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use caolo_sim::prelude::World;
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+use crate::{replay::Recorder, WorldContainer};
+
+const PREFIX: &str = "world_";
+const EXTENSION: &str = "bin";
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Snapshot io failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to (de)serialize the world: {0}")]
+    Serialization(#[from] bincode::Error),
+    #[error("No valid snapshot was found in {0:?}")]
+    NotFound(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshotter {
+    dir: PathBuf,
+    generations: usize,
+    recorder: Recorder,
+    /// Set while a snapshot is being written, so slow disks do not pile up snapshot tasks
+    in_progress: Arc<AtomicBool>,
+}
+
+impl Snapshotter {
+    pub fn new(dir: PathBuf, generations: usize, recorder: Recorder) -> Self {
+        Self {
+            dir,
+            generations: generations.max(1),
+            recorder,
+            in_progress: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Serialize the current world state and write it to disk on a background task.
+    ///
+    /// Skips this snapshot if the previous one is still being written.
+    pub fn spawn_snapshot(&self, world: WorldContainer) {
+        if self.in_progress.swap(true, Ordering::AcqRel) {
+            warn!("Previous snapshot is still in progress, skipping");
+            return;
+        }
+        let this = self.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let world_guard = world.read().await;
+            let time = world_guard.time();
+            let payload = bincode::serialize(&*world_guard);
+            // record under the guard, so no command can sneak in between
+            this.recorder.record_snapshot(time);
+            drop(world_guard);
+
+            let res = match payload {
+                Ok(payload) => {
+                    let dir = this.dir.clone();
+                    let generations = this.generations;
+                    tokio::task::spawn_blocking(move || {
+                        save_snapshot(&dir, time, &payload, generations)
+                    })
+                    .await
+                    .expect("Failed to join snapshot task")
+                }
+                Err(err) => Err(err.into()),
+            };
+            match res {
+                Ok(path) => info!(
+                    "Saved world snapshot of tick {} to {:?} in {:?}",
+                    time,
+                    path,
+                    Instant::now() - start
+                ),
+                Err(err) => error!("Failed to save world snapshot of tick {}: {}", time, err),
+            }
+            this.in_progress.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Write `payload` to `dir` as the snapshot of tick `time`, then remove all but the newest
+/// `generations` snapshots.
+pub fn save_snapshot(
+    dir: &Path,
+    time: u64,
+    payload: &[u8],
+    generations: usize,
+) -> Result<PathBuf, SnapshotError> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(snapshot_file_name(time));
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut f = fs::File::create(&tmp_path)?;
+        f.write_all(payload)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp_path, &path)?;
+
+    let snapshots = list_snapshots(dir)?;
+    let n_stale = snapshots.len().saturating_sub(generations);
+    for (_, stale) in &snapshots[..n_stale] {
+        debug!("Removing stale snapshot {:?}", stale);
+        if let Err(err) = fs::remove_file(stale) {
+            warn!("Failed to remove stale snapshot {:?}: {}", stale, err);
+        }
+    }
+    Ok(path)
+}
+
+/// Load a world from `path`.
+///
+/// If `path` is a directory, snapshots are tried from newest to oldest, and the first one
+/// that deserializes successfully is returned.
+pub fn load_latest(path: &Path) -> Result<World, SnapshotError> {
+    if !path.is_dir() {
+        return load_snapshot(path);
+    }
+    for (time, snapshot) in list_snapshots(path)?.into_iter().rev() {
+        match load_snapshot(&snapshot) {
+            Ok(world) => {
+                info!(
+                    "Restored world snapshot of tick {} from {:?}",
+                    time, snapshot
+                );
+                return Ok(world);
+            }
+            Err(err) => warn!("Failed to load snapshot {:?}: {}", snapshot, err),
+        }
+    }
+    Err(SnapshotError::NotFound(path.to_owned()))
+}
+
+fn load_snapshot(path: &Path) -> Result<World, SnapshotError> {
+    let f = fs::File::open(path)?;
+    let world = bincode::deserialize_from(std::io::BufReader::new(f))?;
+    Ok(world)
+}
+
+fn snapshot_file_name(time: u64) -> String {
+    format!("{}{:020}.{}", PREFIX, time, EXTENSION)
+}
+
+/// List the snapshots in `dir`, ordered by tick, oldest first
+fn list_snapshots(dir: &Path) -> Result<Vec<(u64, PathBuf)>, SnapshotError> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        let time = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_prefix(PREFIX))
+            .and_then(|time| time.parse::<u64>().ok());
+        if let Some(time) = time {
+            snapshots.push((time, path));
+        }
+    }
+    snapshots.sort_unstable();
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("caolo-snapshots-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_keeps_last_generations() {
+        let dir = test_dir();
+
+        for time in 0..5 {
+            save_snapshot(&dir, time, &[time as u8], 2).unwrap();
+        }
+
+        let snapshots = list_snapshots(&dir).unwrap();
+        let times: Vec<_> = snapshots.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, vec![3, 4]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_skips_corrupt_snapshot() {
+        let dir = test_dir();
+
+        let mut exc = caolo_sim::executor::SimpleExecutor;
+        let mut world =
+            futures_lite::future::block_on(exc.initialize(caolo_sim::executor::GameConfig {
+                world_radius: 1,
+                room_radius: 8,
+                ..Default::default()
+            }));
+        caolo_sim::init::init_world_entities(&mut world, 2);
+        let payload = bincode::serialize(&world).unwrap();
+        save_snapshot(&dir, 1, &payload, 3).unwrap();
+        // newer, but truncated snapshot
+        save_snapshot(&dir, 2, &payload[..payload.len() / 2], 3).unwrap();
+
+        let restored = load_latest(&dir).unwrap();
+        assert_eq!(restored.time(), world.time());
+        assert_eq!(restored.queen_tag(), world.queen_tag());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,6 +2,7 @@ use caolo_sim::{
     components::UserProperties,
     prelude::{UserId, View},
 };
+use prost::Message;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::Status;
@@ -11,16 +12,18 @@ use uuid::Uuid;
 use crate::{
     input::users,
     protos::{cao_common, cao_users},
+    replay::{Command, Recorder},
 };
 
 #[derive(Clone)]
 pub struct UsersService {
     world: crate::WorldContainer,
+    recorder: Recorder,
 }
 
 impl UsersService {
-    pub fn new(world: crate::WorldContainer) -> Self {
-        Self { world }
+    pub fn new(world: crate::WorldContainer, recorder: Recorder) -> Self {
+        Self { world, recorder }
     }
 }
 
@@ -93,6 +96,8 @@ impl cao_users::users_server::Users for UsersService {
     ) -> Result<tonic::Response<cao_common::Empty>, tonic::Status> {
         let req = request.get_ref();
         let mut w = self.world.write().await;
+        self.recorder
+            .record_command(Command::RegisterUser(request.get_ref().encode_to_vec()));
         users::register_user(&mut w, req)
             .map(|_: ()| tonic::Response::new(cao_common::Empty {}))
             .map_err(|err| Status::invalid_argument(err.to_string()))
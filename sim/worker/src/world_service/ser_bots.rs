@@ -13,7 +13,12 @@ type BotTables<'a> = (
     View<'a, EntityId, Bot>,
     View<'a, EntityId, CarryComponent>,
     View<'a, EntityId, HpComponent>,
-    View<'a, EntityId, MeleeAttackComponent>,
+    (
+        View<'a, EntityId, MeleeAttackComponent>,
+        View<'a, EntityId, RangedAttackComponent>,
+        View<'a, EntityId, ArmorComponent>,
+        View<'a, EntityId, HealComponent>,
+    ),
     View<'a, EntityId, DecayComponent>,
     View<'a, EntityId, OwnedEntity>,
     View<'a, EntityId, EntityScript>,
@@ -32,7 +37,7 @@ pub fn bot_payload(
         bots,
         carry,
         hp,
-        melee,
+        (melee, ranged, armor, heal),
         decay,
         owner,
         script,
@@ -83,12 +88,12 @@ pub fn bot_payload(
                             value: hp.into(),
                             value_max: hp_max.into(),
                         }),
-                    carry: carry.get(entity_id).copied().map(
-                        |CarryComponent { carry, carry_max }| cao_world::Bounded {
-                            value: carry.into(),
-                            value_max: carry_max.into(),
-                        },
-                    ),
+                    carry: carry
+                        .get(entity_id)
+                        .map(|carry: &CarryComponent| cao_world::Bounded {
+                            value: carry.carry().into(),
+                            value_max: carry.carry_max.into(),
+                        }),
                     decay: decay.get(entity_id).copied().map(
                         |DecayComponent {
                              hp_amount,
@@ -105,6 +110,20 @@ pub fn bot_payload(
                         .copied()
                         .map(|MeleeAttackComponent { strength }| strength.into())
                         .unwrap_or(0),
+                    ranged_strength: ranged
+                        .get(entity_id)
+                        .map(|ranged| ranged.strength.into())
+                        .unwrap_or(0),
+                    armor: armor
+                        .get(entity_id)
+                        .copied()
+                        .map(|ArmorComponent { armor }| armor.into())
+                        .unwrap_or(0),
+                    heal: heal
+                        .get(entity_id)
+                        .copied()
+                        .map(|HealComponent { amount }| amount.into())
+                        .unwrap_or(0),
                     owner: owner.get(entity_id).map(
                         |OwnedEntity {
                              owner_id: UserId(owner_id),
@@ -43,28 +43,31 @@ pub fn resource_payload(
         for (pos, EntityComponent(entity_id)) in entities.iter() {
             let entity_id = *entity_id;
             if let Some(resource) = resource.get(entity_id) {
-                match resource.0 {
-                    Resource::Empty => {}
-                    Resource::Energy => {
-                        accumulator.push(cao_world::Resource {
-                            id: entity_id.into(),
+                // deposits store the amount left in their energy component
+                let ty: fn(cao_world::Bounded) -> cao_world::resource::ResourceType =
+                    match resource.0 {
+                        Resource::Empty => continue,
+                        Resource::Energy => cao_world::resource::ResourceType::Energy,
+                        Resource::Mineral => cao_world::resource::ResourceType::Mineral,
+                        Resource::Crystal => cao_world::resource::ResourceType::Crystal,
+                    };
+                accumulator.push(cao_world::Resource {
+                    id: entity_id.into(),
 
-                            pos: Some(cao_common::WorldPosition {
-                                pos: Some(pos.into()),
-                                room: room.map(|x| x.0.into()),
-                                offset: offset.map(|x| x.into()),
-                            }),
-                            resource_type: energy.get(entity_id).copied().map(
-                                |EnergyComponent { energy, energy_max }: EnergyComponent| {
-                                    cao_world::resource::ResourceType::Energy(cao_world::Bounded {
-                                        value: energy.into(),
-                                        value_max: energy_max.into(),
-                                    })
-                                },
-                            ),
-                        });
-                    }
-                }
+                    pos: Some(cao_common::WorldPosition {
+                        pos: Some(pos.into()),
+                        room: room.map(|x| x.0.into()),
+                        offset: offset.map(|x| x.into()),
+                    }),
+                    resource_type: energy.get(entity_id).copied().map(
+                        |EnergyComponent { energy, energy_max }: EnergyComponent| {
+                            ty(cao_world::Bounded {
+                                value: energy.into(),
+                                value_max: energy_max.into(),
+                            })
+                        },
+                    ),
+                });
             }
         }
     }
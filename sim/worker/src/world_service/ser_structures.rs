@@ -15,6 +15,8 @@ type StructureTables<'a> = (
     View<'a, EntityId, EnergyRegenComponent>,
     View<'a, EntityId, SpawnComponent>,
     View<'a, EntityId, SpawnQueueComponent>,
+    View<'a, EntityId, ConstructionSiteComponent>,
+    View<'a, EntityId, InventoryComponent>,
     WorldTime,
 );
 
@@ -30,6 +32,8 @@ pub fn structure_payload(
         energy_regen,
         spawn,
         spawn_q,
+        construction_site,
+        inventory,
         WorldTime(time),
     ): StructureTables,
 ) {
@@ -111,6 +115,27 @@ pub fn structure_payload(
                                         .unwrap_or_default(),
                                 },
                             ))
+                        } else if let Some(ConstructionSiteComponent(ty)) =
+                            construction_site.get(entity_id)
+                        {
+                            Some(cao_world::structure::StructureBody::ConstructionSite(
+                                cao_world::structure::ConstructionSite {
+                                    structure_type: *ty as u32,
+                                },
+                            ))
+                        } else if let Some(InventoryComponent {
+                            resources,
+                            capacity,
+                        }) = inventory.get(entity_id)
+                        {
+                            Some(cao_world::structure::StructureBody::Container(
+                                cao_world::structure::Container {
+                                    capacity: (*capacity).into(),
+                                    energy: resources.get(Resource::Energy).into(),
+                                    mineral: resources.get(Resource::Mineral).into(),
+                                    crystal: resources.get(Resource::Crystal).into(),
+                                },
+                            ))
                         } else {
                             None
                         }